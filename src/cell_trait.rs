@@ -1,4 +1,4 @@
-use std::{
+use core::{
     cell::Cell,
     ops::{AddAssign, DivAssign, MulAssign, Neg, RemAssign, SubAssign},
 };
@@ -0,0 +1,33 @@
+use crate::Pool;
+use alloc::rc::Rc;
+use core::marker::PhantomData;
+
+/// A [Pool] which allocates each item separately on the heap using [Rc].
+/// Useful for comparison with [crate::RcPool], or for generic code which should work with both.
+pub struct HeapPool<T> {
+    phantom: PhantomData<Rc<T>>,
+}
+
+impl<T> HeapPool<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for HeapPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Pool for HeapPool<T> {
+    type Item = T;
+    type Ref<'t> = Rc<T> where Self: 't;
+
+    fn insert(&self, value: T) -> Self::Ref<'_> {
+        Rc::new(value)
+    }
+}
@@ -1,11 +1,21 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub use cell_trait::*;
 pub use either::*;
+pub use heap_pool::*;
 pub use pool_trait::*;
 pub use rc_pool::*;
 pub use rc_traits::*;
+#[cfg(target_has_atomic = "64")]
+pub use sync_pool::*;
 
 mod cell_trait;
 mod either;
+mod heap_pool;
 mod pool_trait;
 mod rc_pool;
 mod rc_traits;
+#[cfg(target_has_atomic = "64")]
+mod sync_pool;
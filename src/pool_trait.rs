@@ -1,5 +1,6 @@
 use crate::StrongRefTrait;
 
+/// A source of reference counted items, implemented by [crate::RcPool] and [crate::HeapPool]
 pub trait Pool {
     type Item;
 
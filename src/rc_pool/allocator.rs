@@ -0,0 +1,35 @@
+use core::{alloc::Layout, ptr::NonNull};
+
+/// Provides the memory for the pages of an [crate::RcPool]. Each page is one allocation, which is
+/// requested when the page is added and returned when the page is free'd (when the pool is shrunk or dropped).
+///
+/// # Safety
+///
+/// Memory returned by [PageAllocator::allocate] must be valid for reads and writes for the given layout,
+/// and must not be used by anything else until it has been passed to [PageAllocator::deallocate].
+pub unsafe trait PageAllocator {
+    /// Allocates memory for a page, returns None if no memory is available
+    #[must_use]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Returns the memory of a page
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [PageAllocator::allocate] on this allocator with the same layout
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// Allocates pages using the global heap allocator
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct GlobalPageAllocator;
+
+unsafe impl PageAllocator for GlobalPageAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc::alloc::alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::alloc::dealloc(ptr.as_ptr(), layout)
+    }
+}
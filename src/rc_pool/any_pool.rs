@@ -0,0 +1,402 @@
+use super::{
+    page::{FreePageList, PageHeader, MAX_PAGE_SIZE},
+    slot::{drop_slot_item, DropItemFn, SlotHeader},
+};
+use crate::{
+    unsize, Either, GlobalPageAllocator, Index, PageAllocator, PoolError, StrongRef,
+    StrongRefTrait, Version, WeakRef, WeakRefTrait,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    alloc::Layout,
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    mem::{align_of, size_of},
+    ops::Deref,
+    ptr::{null, NonNull},
+};
+
+/// Returns the destructor of an [AnyPool] slot's item, which is stored right before the slot header
+#[must_use]
+unsafe fn item_drop_fn(slot: NonNull<SlotHeader>) -> *mut DropItemFn {
+    slot.cast::<DropItemFn>().as_ptr().sub(1)
+}
+
+/// Page destructor of [AnyPool] pages, which calls the destructor of the slot's item type
+unsafe fn drop_any_item(slot: NonNull<SlotHeader>) {
+    (*item_drop_fn(slot))(slot)
+}
+
+/// Pages for items of one size and alignment class. Each slot consists of the item destructor,
+/// the slot header and space for the item, aligned so that the item is at the class alignment.
+struct SizeClass {
+    free_pages: FreePageList, // Null if all pages are full
+    pages: RefCell<Vec<NonNull<PageHeader>>>,
+    item_size: usize,
+    item_align: usize,
+    first_slot: u32,
+    slot_size: u32,
+    occupancy: u32,
+    page_layout: Layout,
+}
+
+impl SizeClass {
+    /// Returns the size and alignment class of items with the given layout
+    #[must_use]
+    fn of(layout: Layout) -> (usize, usize) {
+        let align = layout.align().max(align_of::<SlotHeader>());
+        (layout.size().max(align).next_power_of_two(), align)
+    }
+
+    /// Computes the slot layout of a class, returns None if a page would be too large for the slot offsets
+    #[must_use]
+    fn new(item_size: usize, item_align: usize, page_len: Index) -> Option<Self> {
+        let header_size = size_of::<DropItemFn>() + size_of::<SlotHeader>();
+        let first_item =
+            (size_of::<PageHeader>() + header_size).checked_next_multiple_of(item_align)?;
+        let slot_size = (header_size + item_size).checked_next_multiple_of(item_align)?;
+
+        let page_size = slot_size
+            .checked_mul(page_len as usize)?
+            .checked_add(first_item - header_size)?;
+
+        let (page_layout, occupancy) =
+            Layout::from_size_align(page_size, item_align.max(align_of::<PageHeader>()))
+                .ok()?
+                .extend(PageHeader::occupancy_layout(page_len)?)
+                .ok()?;
+
+        if page_layout.size() > MAX_PAGE_SIZE {
+            return None;
+        }
+
+        Some(Self {
+            free_pages: Cell::new(null()),
+            pages: Default::default(),
+            item_size,
+            item_align,
+            first_slot: (first_item - size_of::<SlotHeader>()) as u32,
+            slot_size: slot_size as u32,
+            occupancy: occupancy as u32,
+            page_layout,
+        })
+    }
+
+    fn first_free_page(&self) -> Option<&PageHeader> {
+        unsafe { self.free_pages.get().as_ref() }
+    }
+
+    fn pop_free_page(&self) {
+        if let Some(page) = self.first_free_page() {
+            self.free_pages.set(page.next_free_page.get());
+            page.next_free_page.set(null());
+        }
+    }
+
+    /// Adds a page of `page_len` slots with the given first slot id, and puts it first in the free page list
+    fn add_page(
+        &self,
+        allocator: &dyn PageAllocator,
+        page_len: Index,
+        first_id: Index,
+    ) -> Result<(), PoolError> {
+        let page = allocator
+            .allocate(self.page_layout)
+            .ok_or(PoolError::OutOfMemory)?;
+
+        unsafe {
+            PageHeader::init_raw(
+                page,
+                &self.free_pages,
+                page_len,
+                first_id,
+                Version::MIN,
+                self.first_slot,
+                self.slot_size,
+                self.occupancy,
+                drop_any_item,
+            )
+        };
+
+        let page = page.cast::<PageHeader>();
+        self.pages.borrow_mut().push(page);
+        unsafe { page.as_ref() }
+            .next_free_page
+            .set(self.free_pages.get());
+        self.free_pages.set(page.as_ptr());
+        Ok(())
+    }
+}
+
+/// A pool of items of any types. Items with similar size and alignment share pages, so that many small types
+/// don't each need their own partly empty pages. Items are accessed through the same [StrongRef] and [WeakRef]
+/// types as in an [crate::RcPool], or through type-erased [AnyStrongRef] and [AnyWeakRef] handles.
+pub struct AnyPool<const MANUAL_DROP: bool> {
+    #[allow(clippy::vec_box)]
+    // Pages point to the free page list of their class, so classes must not move
+    classes: RefCell<Vec<Box<SizeClass>>>,
+    page_len: Index,
+    next_id: Cell<Index>, // First slot id of the next added page
+    allocator: Box<dyn PageAllocator>,
+}
+
+impl<const MANUAL_DROP: bool> AnyPool<MANUAL_DROP> {
+    /// Creates a pool whose pages have `page_len` slots. No pages are allocated until items are inserted.
+    #[must_use]
+    pub fn new(page_len: Index) -> Self {
+        Self::new_in(page_len, GlobalPageAllocator)
+    }
+
+    /// Creates a pool which allocates its pages from the given allocator
+    #[must_use]
+    pub fn new_in(page_len: Index, allocator: impl PageAllocator + 'static) -> Self {
+        Self {
+            classes: Default::default(),
+            page_len: page_len.max(1),
+            next_id: Default::default(),
+            allocator: Box::new(allocator),
+        }
+    }
+
+    #[must_use]
+    pub fn page_len(&self) -> Index {
+        self.page_len
+    }
+
+    /// Returns the size class for items with the given layout, adding it if it doesn't exist
+    fn class(&self, layout: Layout) -> Result<&SizeClass, PoolError> {
+        let (size, align) = SizeClass::of(layout);
+        let mut classes = self.classes.borrow_mut();
+
+        let class = match classes
+            .iter()
+            .find(|c| c.item_size == size && c.item_align == align)
+        {
+            Some(class) => class,
+
+            None => {
+                let class =
+                    SizeClass::new(size, align, self.page_len).ok_or(PoolError::PageTooLarge)?;
+                classes.push(Box::new(class));
+                classes.last().unwrap()
+            }
+        };
+
+        // Classes are boxed and never removed before the pool is dropped
+        Ok(unsafe { &*(&**class as *const SizeClass) })
+    }
+
+    /// Returns a page with a free slot for an item with the given layout, adding a page if needed
+    fn free_page(&self, layout: Layout) -> Result<(&SizeClass, &PageHeader), PoolError> {
+        let class = self.class(layout)?;
+
+        if class.first_free_page().is_none() {
+            let next_id = self
+                .next_id
+                .get()
+                .checked_add(self.page_len)
+                .ok_or(PoolError::CapacityExceeded)?;
+
+            class.add_page(&*self.allocator, self.page_len, self.next_id.get())?;
+            self.next_id.set(next_id);
+        }
+
+        Ok((class, class.first_free_page().unwrap()))
+    }
+
+    /// Inserts a new item into the pool, adding a page to its size class if needed.
+    /// If a page can't be added the item is returned.
+    #[must_use]
+    pub fn try_insert<T: 'static>(&self, value: T) -> Either<StrongRef<'_, T, MANUAL_DROP>, T> {
+        let Ok((class, page)) = self.free_page(Layout::new::<T>()) else {
+            return Either::Right(value);
+        };
+
+        let slot = unsafe { page.insert(value) };
+        unsafe { item_drop_fn(NonNull::from(slot)).write(drop_slot_item::<T>) };
+
+        if page.is_full() {
+            class.pop_free_page();
+        }
+
+        Either::Left(StrongRef::new(slot.item()))
+    }
+
+    /// Inserts a new item into the pool, adding a page to its size class if needed.
+    /// Panics if a page can't be added.
+    #[must_use]
+    pub fn insert<T: 'static>(&self, value: T) -> StrongRef<'_, T, MANUAL_DROP> {
+        if let Err(e) = self.free_page(Layout::new::<T>()) {
+            panic!("{e}")
+        }
+
+        match self.try_insert(value) {
+            Either::Left(r) => r,
+            Either::Right(_) => unreachable!("No free slot!"),
+        }
+    }
+
+    /// Inserts a new item into the pool and returns a type-erased reference to it, see [AnyPool::insert]
+    #[must_use]
+    pub fn insert_any<T: 'static>(&self, value: T) -> AnyStrongRef<'_, MANUAL_DROP> {
+        self.insert(value).into()
+    }
+
+    /// Returns the number of items in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sum_pages(|p| p.len() as usize)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total number of slots in all pages
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.sum_pages(|p| p.capacity() as usize)
+    }
+
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.sum_pages(|_| 1)
+    }
+
+    /// Returns the number of size and alignment classes which have pages
+    #[must_use]
+    pub fn class_count(&self) -> usize {
+        self.classes.borrow().len()
+    }
+
+    fn sum_pages(&self, f: impl Fn(&PageHeader) -> usize) -> usize {
+        self.classes
+            .borrow()
+            .iter()
+            .flat_map(|c| c.pages.borrow().clone())
+            .map(|p| f(unsafe { p.as_ref() }))
+            .sum()
+    }
+}
+
+impl<const MANUAL_DROP: bool> Drop for AnyPool<MANUAL_DROP> {
+    /// Drops all remaining items, regardless of whether there are weak references to them,
+    /// and frees all pages
+    fn drop(&mut self) {
+        for class in self.classes.get_mut().iter_mut() {
+            for page in class.pages.get_mut().drain(..) {
+                unsafe {
+                    page.as_ref().drop_items();
+                    self.allocator.deallocate(page.cast(), class.page_layout);
+                }
+            }
+        }
+    }
+}
+
+/// A type-erased strong reference to a pool item, which dereferences to `dyn Any` and can be converted back to a
+/// typed [StrongRef] with [AnyStrongRef::downcast]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct AnyStrongRef<'t, const MANUAL_DROP: bool> {
+    r: StrongRef<'t, dyn Any, MANUAL_DROP>,
+}
+
+impl<'t, const MANUAL_DROP: bool> AnyStrongRef<'t, MANUAL_DROP> {
+    /// Returns the typed reference if the item is of type `T`, otherwise returns `self`
+    pub fn downcast<T: 'static>(self) -> Result<StrongRef<'t, T, MANUAL_DROP>, Self> {
+        if (*self.r).type_id() == TypeId::of::<T>() {
+            Ok(unsafe { self.r.cast() })
+        } else {
+            Err(self)
+        }
+    }
+
+    #[must_use]
+    pub fn weak(&self) -> AnyWeakRef<'t, MANUAL_DROP> {
+        AnyWeakRef {
+            r: self.r.weak(),
+            type_id: (*self.r).type_id(),
+        }
+    }
+
+    #[must_use]
+    pub fn strong_count(&self) -> usize {
+        self.r.strong_count()
+    }
+
+    #[must_use]
+    pub fn into_strong(self) -> StrongRef<'t, dyn Any, MANUAL_DROP> {
+        self.r
+    }
+}
+
+impl<'t, const MANUAL_DROP: bool> Deref for AnyStrongRef<'t, MANUAL_DROP> {
+    type Target = dyn Any;
+    fn deref(&self) -> &Self::Target {
+        &*self.r
+    }
+}
+
+impl<'t, T: 'static, const MANUAL_DROP: bool> From<StrongRef<'t, T, MANUAL_DROP>>
+    for AnyStrongRef<'t, MANUAL_DROP>
+{
+    fn from(r: StrongRef<'t, T, MANUAL_DROP>) -> Self {
+        Self {
+            r: unsize!(r => dyn Any),
+        }
+    }
+}
+
+/// A type-erased weak reference to a pool item, which can be converted back to a typed [WeakRef] with
+/// [AnyWeakRef::downcast], also after the item has been removed
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AnyWeakRef<'t, const MANUAL_DROP: bool> {
+    r: WeakRef<'t, dyn Any, MANUAL_DROP>,
+    type_id: TypeId,
+}
+
+impl<'t, const MANUAL_DROP: bool> AnyWeakRef<'t, MANUAL_DROP> {
+    /// Returns the typed reference if the item is of type `T`, otherwise returns `self`
+    pub fn downcast<T: 'static>(self) -> Result<WeakRef<'t, T, MANUAL_DROP>, Self> {
+        if self.type_id == TypeId::of::<T>() {
+            Ok(unsafe { self.r.cast() })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Returns the type id of the item, which is known also after the item has been removed
+    #[must_use]
+    pub fn item_type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Returns a strong reference to the item, or None if it has been removed. Panics if the item is mutably
+    /// borrowed, see [WeakRef::try_strong].
+    #[must_use]
+    pub fn strong(&self) -> Option<AnyStrongRef<'t, MANUAL_DROP>> {
+        self.r.strong().map(|r| AnyStrongRef { r })
+    }
+
+    /// Returns a strong reference to the item, or an error if it has been removed or is mutably borrowed
+    pub fn try_strong(&self) -> Result<AnyStrongRef<'t, MANUAL_DROP>, PoolError> {
+        self.r.try_strong().map(|r| AnyStrongRef { r })
+    }
+
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.r.is_valid()
+    }
+}
+
+impl<'t, T: 'static, const MANUAL_DROP: bool> From<WeakRef<'t, T, MANUAL_DROP>>
+    for AnyWeakRef<'t, MANUAL_DROP>
+{
+    fn from(r: WeakRef<'t, T, MANUAL_DROP>) -> Self {
+        Self {
+            r: unsize!(r => dyn Any),
+            type_id: TypeId::of::<T>(),
+        }
+    }
+}
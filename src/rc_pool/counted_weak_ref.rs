@@ -0,0 +1,120 @@
+use crate::{CellTrait, Key, PoolError, StrongRef, WeakRef, WeakRefTrait};
+
+/// A weak reference which is counted per page, unlike a [WeakRef], so that [crate::RcPool::shrink_to_fit] can
+/// remove the page while the reference exists. The reference is then invalid, and the page's memory is free'd
+/// once all counted weak references into it have been dropped. It can be cloned but isn't Copy.
+pub struct CountedWeakRef<'t, T: ?Sized, const MANUAL_DROP: bool> {
+    r: WeakRef<'t, T, MANUAL_DROP>, // Never handed out, since it doesn't mark the page as having weak references
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> CountedWeakRef<'t, T, MANUAL_DROP> {
+    /// Counts a weak reference, which must have been created with [WeakRef::new_untracked]
+    #[must_use]
+    pub(crate) fn new(r: WeakRef<'t, T, MANUAL_DROP>) -> Self {
+        r.slot().page().weak_count().add(1);
+        Self { r }
+    }
+
+    /// Returns a strong reference to the item, or an error if it has been removed or is mutably borrowed
+    pub fn try_strong(&self) -> Result<StrongRef<'t, T, MANUAL_DROP>, PoolError> {
+        self.r.try_strong()
+    }
+
+    /// Removes and drops the item with the destructor of its concrete type, if there are no strong references to it
+    pub fn try_drop_item(&self) -> Result<(), PoolError> {
+        self.r.try_drop_item()
+    }
+
+    pub fn drop_item(&self) {
+        self.r.drop_item()
+    }
+
+    /// Returns a compact key identifying the same item, which can be resolved through the pool
+    #[must_use]
+    pub fn key(&self) -> Key {
+        self.r.key()
+    }
+
+    /// Returns an uncounted weak reference to the item, which keeps its page from being removed
+    #[must_use]
+    pub fn weak(&self) -> WeakRef<'t, T, MANUAL_DROP> {
+        self.r.slot().page().weak_refs().set(true);
+        self.r
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> CountedWeakRef<'t, T, MANUAL_DROP> {
+    /// Removes the item from the pool and returns it, if there are no strong references to it and it isn't pinned
+    pub fn try_take_item(&self) -> Result<T, PoolError> {
+        self.r.try_take_item()
+    }
+
+    #[must_use]
+    pub fn take_item(&self) -> T {
+        self.r.take_item()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> WeakRefTrait for CountedWeakRef<'t, T, MANUAL_DROP> {
+    type Target = T;
+    type Strong = StrongRef<'t, T, MANUAL_DROP>;
+
+    /// Panics if the item is mutably borrowed, see [CountedWeakRef::try_strong]
+    fn strong(&self) -> Option<Self::Strong> {
+        self.r.strong()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.r.is_valid()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> From<StrongRef<'t, T, MANUAL_DROP>>
+    for CountedWeakRef<'t, T, MANUAL_DROP>
+{
+    fn from(r: StrongRef<'t, T, MANUAL_DROP>) -> Self {
+        r.counted_weak()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> From<CountedWeakRef<'t, T, MANUAL_DROP>> for Key {
+    fn from(r: CountedWeakRef<'t, T, MANUAL_DROP>) -> Self {
+        r.key()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> PartialEq for CountedWeakRef<'t, T, MANUAL_DROP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.r == other.r
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Eq for CountedWeakRef<'t, T, MANUAL_DROP> {}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Clone for CountedWeakRef<'t, T, MANUAL_DROP> {
+    fn clone(&self) -> Self {
+        Self::new(self.r)
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Drop for CountedWeakRef<'t, T, MANUAL_DROP> {
+    fn drop(&mut self) {
+        self.r.slot().page().weak_count().sub(1);
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> core::hash::Hash
+    for CountedWeakRef<'t, T, MANUAL_DROP>
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.r.hash(state);
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> core::fmt::Debug
+    for CountedWeakRef<'t, T, MANUAL_DROP>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CountedWeakRef").field(&self.r).finish()
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{PoolLimit, PoolLimits};
+use core::fmt::{Debug, Display, Formatter};
+
+/// Returned when an item can't be inserted because the pool couldn't grow, either since it has reached its
+/// [PoolLimits] ([PoolError::CapacityExceeded]) or since a page couldn't be allocated ([PoolError::OutOfMemory]).
+/// Contains the item that couldn't be inserted.
+pub struct PoolFull<T> {
+    pub value: T,
+    pub limits: PoolLimits,
+    pub reason: PoolError,
+    /// The limit that was reached, None if the pool couldn't grow for another reason
+    pub limit: Option<PoolLimit>,
+}
+
+impl<T> PoolFull<T> {
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Debug for PoolFull<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PoolFull")
+            .field("limits", &self.limits)
+            .field("reason", &self.reason)
+            .field("limit", &self.limit)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Display for PoolFull<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.limit {
+            Some(PoolLimit::Capacity(slots)) => {
+                write!(
+                    f,
+                    "Pool is full, it has reached its limit of {slots} slots!"
+                )
+            }
+            Some(PoolLimit::Pages(pages)) => {
+                write!(
+                    f,
+                    "Pool is full, it has reached its limit of {pages} pages!"
+                )
+            }
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for PoolFull<T> {}
+
+/// Reasons why an operation on a pool or a reference failed, returned by the `try_` methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoolError {
+    /// The item has been removed from its slot
+    Removed,
+    /// The item is mutably borrowed through a `RefMut` or `CellRefMut`
+    MutablyBorrowed,
+    /// The item can't be mutably borrowed since it's borrowed through a `CellRef`
+    Borrowed,
+    /// The operation requires a unique strong reference (or none for weak references),
+    /// but the item has `strong_count` strong references
+    NotUnique { strong_count: usize },
+    /// The item is pinned, so it can't be moved out of the pool or mutably borrowed without pinning
+    Pinned,
+    /// The pool can't grow without exceeding its [PoolLimits], or the slot ids have run out
+    CapacityExceeded,
+    /// A page of the item type with the requested number of slots would exceed the maximum page size
+    PageTooLarge,
+    /// The page allocator couldn't allocate a new page
+    OutOfMemory,
+}
+
+impl Display for PoolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PoolError::Removed => write!(f, "Element removed!"),
+            PoolError::MutablyBorrowed => write!(f, "Already borrowed as mutable!"),
+            PoolError::Borrowed => write!(f, "Already borrowed!"),
+            PoolError::NotUnique { strong_count } => {
+                write!(f, "Item has {strong_count} strong references!")
+            }
+            PoolError::Pinned => write!(f, "Item is pinned!"),
+            PoolError::CapacityExceeded => write!(f, "Pool is full!"),
+            PoolError::PageTooLarge => write!(f, "Page too large!"),
+            PoolError::OutOfMemory => write!(f, "Out of page memory!"),
+        }
+    }
+}
+
+impl<T> From<PoolFull<T>> for PoolError {
+    fn from(e: PoolFull<T>) -> Self {
+        e.reason
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PoolError {}
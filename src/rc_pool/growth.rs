@@ -0,0 +1,122 @@
+use crate::Index;
+use core::mem::size_of;
+
+/// The state of a pool when it needs to add a new page
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct GrowthContext {
+    /// Total number of slots in all current pages
+    pub capacity: usize,
+    pub page_count: usize,
+
+    /// Number of slots in the most recently added page
+    pub last_page_len: Index,
+
+    /// Size in bytes of one slot, see [crate::RcPool::slot_overhead]
+    pub slot_size: usize,
+
+    /// Size in bytes of the page header, padded to the slot alignment
+    pub header_size: usize,
+}
+
+impl GrowthContext {
+    /// Returns the size in bytes of a page with `len` slots, including the page header and the occupancy bitmap
+    #[must_use]
+    pub fn page_size(&self, len: Index) -> usize {
+        let bitmap_words = len.div_ceil(usize::BITS) as usize;
+        self.header_size + len as usize * self.slot_size + bitmap_words * size_of::<usize>()
+    }
+}
+
+/// Decides the number of slots in new pages added to an [crate::RcPool].
+/// Implemented for closures taking a [GrowthContext].
+pub trait GrowthPolicy {
+    /// Returns the number of slots for a new page, zero is treated as one
+    #[must_use]
+    fn next_page_len(&self, context: &GrowthContext) -> Index;
+}
+
+impl<F: Fn(&GrowthContext) -> Index> GrowthPolicy for F {
+    fn next_page_len(&self, context: &GrowthContext) -> Index {
+        self(context)
+    }
+}
+
+/// All new pages have the same number of slots
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct FixedGrowth(pub Index);
+
+impl GrowthPolicy for FixedGrowth {
+    fn next_page_len(&self, _context: &GrowthContext) -> Index {
+        self.0
+    }
+}
+
+/// Each new page has as many slots as all current pages together, so the capacity doubles,
+/// until pages reach `max_page_len` slots
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct GeometricGrowth {
+    pub max_page_len: Index,
+}
+
+impl GrowthPolicy for GeometricGrowth {
+    fn next_page_len(&self, context: &GrowthContext) -> Index {
+        context.capacity.min(self.max_page_len as usize) as Index
+    }
+}
+
+/// Each new page uses at most `page_bytes` bytes of memory, including the page header and the occupancy bitmap,
+/// see [GrowthContext::page_size]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct ByteBudgetGrowth {
+    pub page_bytes: usize,
+}
+
+impl GrowthPolicy for ByteBudgetGrowth {
+    fn next_page_len(&self, context: &GrowthContext) -> Index {
+        // Each slot also takes one bit of the bitmap, which is only rounded up to whole words below
+        let slot_bits = context.slot_size as u128 * 8 + 1;
+        let budget = self.page_bytes.saturating_sub(context.header_size) as u128 * 8;
+        let mut len = (budget / slot_bits).min(Index::MAX as u128) as Index;
+
+        while len > 0 && context.page_size(len) > self.page_bytes {
+            len -= 1;
+        }
+
+        len
+    }
+}
+
+/// Upper bounds on the size of a pool, no limits by default.
+/// When a limit is reached the pool refuses to add more pages, the last page is made smaller if
+/// needed to not exceed `max_capacity`.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct PoolLimits {
+    /// Maximum total number of slots in all pages
+    pub max_capacity: Option<usize>,
+
+    /// Maximum number of pages
+    pub max_pages: Option<usize>,
+}
+
+impl PoolLimits {
+    /// Returns the limit that keeps a pool with the given number of pages and slots from adding a page, if any
+    #[must_use]
+    pub(crate) fn reached(&self, page_count: usize, capacity: usize) -> Option<PoolLimit> {
+        match (self.max_pages, self.max_capacity) {
+            (Some(max_pages), _) if page_count >= max_pages => Some(PoolLimit::Pages(max_pages)),
+            (_, Some(max_capacity)) if capacity >= max_capacity => {
+                Some(PoolLimit::Capacity(max_capacity))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One of the [PoolLimits] with its value, see [crate::PoolFull]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PoolLimit {
+    /// [PoolLimits::max_capacity] slots
+    Capacity(usize),
+    /// [PoolLimits::max_pages] pages
+    Pages(usize),
+}
@@ -0,0 +1,379 @@
+use super::{
+    page::{PageHeader, PageIterator},
+    slot::SlotHeader,
+};
+use crate::{CellTrait, Index, StrongRef, WeakRef, MUT_REF_COUNT};
+use core::{cell::Cell, marker::PhantomData, ptr};
+
+/// Counts an iterator in its pool's iterator count while it's alive, so that [crate::RcPool::shrink_to_fit]
+/// doesn't unlink or free any pages the iterator may still visit
+pub(crate) struct IterGuard<'t>(Option<&'t Cell<usize>>);
+
+impl<'t> IterGuard<'t> {
+    #[must_use]
+    pub(crate) fn new(iterators: Option<&'t Cell<usize>>) -> Self {
+        if let Some(iterators) = iterators {
+            iterators.add(1);
+        }
+
+        Self(iterators)
+    }
+}
+
+impl<'t> Drop for IterGuard<'t> {
+    fn drop(&mut self) {
+        if let Some(iterators) = self.0 {
+            iterators.sub(1);
+        }
+    }
+}
+
+/// Iterates over the occupied slots of a page and all pages after it, from both ends. Free slots are skipped
+/// a bitmap word at a time, so iterating takes time proportional to the number of live items rather than
+/// the pool capacity.
+pub(crate) struct SlotIterator<'t> {
+    front: Option<&'t PageHeader>, // None if the iterator is exhausted
+    back: Option<&'t PageHeader>,
+    start: Index, // Next index on the front page
+    end: Index,   // Index after the next index on the back page
+    live: usize,
+    borrowed: usize,
+    _guard: IterGuard<'t>,
+}
+
+impl<'t> SlotIterator<'t> {
+    /// Creates an iterator over a page and all pages after it. `iterators` is the iterator count of the pool,
+    /// if the pages can be unlinked while the iterator is alive.
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>, iterators: Option<&'t Cell<usize>>) -> Self {
+        let mut back = page;
+        let mut live = 0;
+        let mut borrowed = 0;
+
+        for p in PageIterator::new(page) {
+            live += p.len() as usize;
+            borrowed += p.borrowed().get() as usize;
+            back = Some(p);
+        }
+
+        Self {
+            front: page,
+            back,
+            start: 0,
+            end: back.map_or(0, |p| p.capacity()),
+            live,
+            borrowed,
+            _guard: IterGuard::new(iterators),
+        }
+    }
+
+    /// Creates an iterator over the occupied slots of a single page
+    #[must_use]
+    pub(crate) fn page(page: &'t PageHeader) -> Self {
+        Self {
+            front: Some(page),
+            back: Some(page),
+            start: 0,
+            end: page.capacity(),
+            live: page.len() as usize,
+            borrowed: page.borrowed().get() as usize,
+            _guard: IterGuard::new(None),
+        }
+    }
+
+    /// Returns the number of remaining live items, exact unless the pool has been modified during iteration
+    #[must_use]
+    pub(crate) fn live(&self) -> usize {
+        self.live
+    }
+
+    /// Returns the number of remaining mutably borrowed items, exact unless the pool has been modified
+    /// during iteration
+    #[must_use]
+    pub(crate) fn borrowed(&self) -> usize {
+        self.borrowed
+    }
+
+    fn yielded(&mut self, slot: &SlotHeader) {
+        self.live = self.live.saturating_sub(1);
+
+        if slot.count.get() == MUT_REF_COUNT {
+            self.borrowed = self.borrowed.saturating_sub(1);
+        }
+    }
+
+    fn is_last_page(&self, page: &PageHeader) -> bool {
+        self.back.is_some_and(|p| ptr::eq(p, page))
+    }
+
+    fn finish(&mut self) {
+        self.front = None;
+        self.back = None;
+    }
+}
+
+impl<'t> Iterator for SlotIterator<'t> {
+    type Item = &'t SlotHeader;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(page) = self.front {
+            let last = self.is_last_page(page);
+            let end = if last { self.end } else { page.capacity() };
+
+            if let Some(i) = page.next_occupied(self.start, end) {
+                self.start = i + 1;
+                let slot = unsafe { page.slot(i) };
+                self.yielded(slot);
+                return Some(slot);
+            } else if last {
+                self.finish();
+            } else {
+                self.front = page.next_page();
+                self.start = 0;
+            }
+        }
+
+        None
+    }
+}
+
+impl<'t> DoubleEndedIterator for SlotIterator<'t> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some(page) = self.back {
+            let first = self.front.is_some_and(|p| ptr::eq(p, page));
+            let start = if first { self.start } else { 0 };
+
+            if let Some(i) = page.prev_occupied(start, self.end) {
+                self.end = i;
+                let slot = unsafe { page.slot(i) };
+                self.yielded(slot);
+                return Some(slot);
+            } else if first {
+                self.finish();
+            } else {
+                self.back = page.prev_page();
+                self.end = self.back.map_or(0, |p| p.capacity());
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterates over strong references to all items of a pool, except those that are mutably borrowed
+pub struct RcPoolIterator<'t, T, const MANUAL_DROP: bool> {
+    slots: SlotIterator<'t>,
+    phantom: PhantomData<&'t mut T>,
+}
+
+impl<'t, T, const MANUAL_DROP: bool> RcPoolIterator<'t, T, MANUAL_DROP> {
+    /// Creates an iterator over a page and all pages after it
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>, iterators: Option<&'t Cell<usize>>) -> Self {
+        Self {
+            slots: SlotIterator::new(page, iterators),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> Iterator for RcPoolIterator<'t, T, MANUAL_DROP> {
+    type Item = StrongRef<'t, T, MANUAL_DROP>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .find(|s| s.count.get() != MUT_REF_COUNT)
+            .map(|s| StrongRef::new(s.item()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> DoubleEndedIterator
+    for RcPoolIterator<'t, T, MANUAL_DROP>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots
+            .rfind(|s| s.count.get() != MUT_REF_COUNT)
+            .map(|s| StrongRef::new(s.item()))
+    }
+}
+
+/// The length is exact unless the pool is modified during iteration
+impl<'t, T: 't, const MANUAL_DROP: bool> ExactSizeIterator for RcPoolIterator<'t, T, MANUAL_DROP> {
+    fn len(&self) -> usize {
+        self.slots.live().saturating_sub(self.slots.borrowed())
+    }
+}
+
+/// Iterates over weak references to all items of a pool, see [crate::RcPool::iter_weak]
+pub struct WeakRefIterator<'t, T, const MANUAL_DROP: bool> {
+    slots: SlotIterator<'t>,
+    phantom: PhantomData<&'t mut T>,
+}
+
+impl<'t, T, const MANUAL_DROP: bool> WeakRefIterator<'t, T, MANUAL_DROP> {
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>, iterators: Option<&'t Cell<usize>>) -> Self {
+        Self {
+            slots: SlotIterator::new(page, iterators),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> Iterator for WeakRefIterator<'t, T, MANUAL_DROP> {
+    type Item = WeakRef<'t, T, MANUAL_DROP>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots.next().map(|s| WeakRef::new(s.item()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.slots.live(), Some(self.slots.live()))
+    }
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> DoubleEndedIterator
+    for WeakRefIterator<'t, T, MANUAL_DROP>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots.next_back().map(|s| WeakRef::new(s.item()))
+    }
+}
+
+/// The length is exact unless the pool is modified during iteration
+impl<'t, T: 't, const MANUAL_DROP: bool> ExactSizeIterator for WeakRefIterator<'t, T, MANUAL_DROP> {}
+
+/// Iterates over shared references to all items of a pool, see [crate::RcPool::values]
+pub struct ValueIterator<'t, T> {
+    slots: SlotIterator<'t>,
+    phantom: PhantomData<&'t T>,
+}
+
+impl<'t, T> ValueIterator<'t, T> {
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>) -> Self {
+        Self {
+            slots: SlotIterator::new(page, None),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates an iterator over the items of a single page
+    #[must_use]
+    pub(crate) fn page(page: &'t PageHeader) -> Self {
+        Self {
+            slots: SlotIterator::page(page),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'t, T: 't> Iterator for ValueIterator<'t, T> {
+    type Item = &'t T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots.next().map(|s| unsafe { s.item().as_ref() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.slots.live(), Some(self.slots.live()))
+    }
+}
+
+impl<'t, T: 't> DoubleEndedIterator for ValueIterator<'t, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots.next_back().map(|s| unsafe { s.item().as_ref() })
+    }
+}
+
+impl<'t, T: 't> ExactSizeIterator for ValueIterator<'t, T> {}
+
+/// Iterates over mutable references to all items of a pool that aren't pinned, see [crate::RcPool::iter_mut]
+pub struct ValueMutIterator<'t, T> {
+    slots: SlotIterator<'t>,
+    phantom: PhantomData<&'t mut T>,
+}
+
+impl<'t, T> ValueMutIterator<'t, T> {
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>) -> Self {
+        Self {
+            slots: SlotIterator::new(page, None),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates an iterator over the items of a single page
+    #[must_use]
+    pub(crate) fn page(page: &'t PageHeader) -> Self {
+        Self {
+            slots: SlotIterator::page(page),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'t, T: 't> Iterator for ValueMutIterator<'t, T> {
+    type Item = &'t mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .find(|s| !s.is_pinned())
+            .map(|s| unsafe { s.item().as_mut() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.slots.live()))
+    }
+}
+
+impl<'t, T: 't> DoubleEndedIterator for ValueMutIterator<'t, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots
+            .rfind(|s| !s.is_pinned())
+            .map(|s| unsafe { s.item().as_mut() })
+    }
+}
+
+/// Removes and yields the items of a pool that have no strong references, see [crate::RcPool::drain]
+pub struct DrainIterator<'t, T, const MANUAL_DROP: bool> {
+    slots: SlotIterator<'t>,
+    phantom: PhantomData<&'t mut T>,
+}
+
+impl<'t, T, const MANUAL_DROP: bool> DrainIterator<'t, T, MANUAL_DROP> {
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>, iterators: Option<&'t Cell<usize>>) -> Self {
+        Self {
+            slots: SlotIterator::new(page, iterators),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> Iterator for DrainIterator<'t, T, MANUAL_DROP> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .find(|s| s.count.get() == 0 && !s.is_pinned())
+            .map(|s| unsafe { s.take_item() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.slots.live()))
+    }
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> DoubleEndedIterator for DrainIterator<'t, T, MANUAL_DROP> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots
+            .rfind(|s| s.count.get() == 0 && !s.is_pinned())
+            .map(|s| unsafe { s.take_item() })
+    }
+}
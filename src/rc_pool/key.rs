@@ -0,0 +1,51 @@
+use super::slot::SlotHeader;
+use crate::{Index, Version};
+
+/// A compact (8 byte) identifier of a pool item, consisting of a pool wide slot id and the lower 32 bits of the
+/// slot version. Unlike a [crate::WeakRef] it doesn't reference the slot, so it must be resolved through the pool
+/// it was created from (see [crate::RcPool::get]), which makes it suitable for dense storage and serialization.
+///
+/// Since only 32 bits of the version are stored, a key could resolve to a newer item if its slot has been re-used
+/// 2^31 times. The slot ids of pages removed by [crate::RcPool::shrink_to_fit] are re-used by pages added later,
+/// whose slot versions continue from the removed pages, so keys into removed pages don't resolve to new items.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Key {
+    id: Index,
+    version: u32,
+}
+
+impl Key {
+    #[must_use]
+    pub(crate) fn new(slot: &SlotHeader, version: Version) -> Self {
+        Self {
+            id: slot.page().slot_id(slot),
+            version: version.get() as u32,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn id(&self) -> Index {
+        self.id
+    }
+
+    /// Returns true if the slot contains the item this key was created for
+    #[must_use]
+    pub(crate) fn matches(&self, slot: &SlotHeader) -> bool {
+        !slot.is_free() && slot.version.get().get() as u32 == self.version
+    }
+
+    /// Returns the key as a single integer, for example for serialization
+    #[must_use]
+    pub fn to_bits(self) -> u64 {
+        (self.id as u64) << 32 | self.version as u64
+    }
+
+    /// Creates a key from an integer returned by [Key::to_bits]
+    #[must_use]
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            id: (bits >> 32) as Index,
+            version: bits as u32,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{RefMut, StrongRef};
+use core::{
+    ops::{Deref, DerefMut},
+    pin::Pin,
+};
+
+/// A strong reference to a part of a pool item, created with [StrongRef::map].
+/// Keeps the item alive in the same way as the strong reference it was created from.
+pub struct MappedRef<'t, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> {
+    r: StrongRef<'t, T, MANUAL_DROP>,
+    value: *const U,
+}
+
+impl<'t, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> MappedRef<'t, T, U, MANUAL_DROP> {
+    /// Maps the reference to a part of the referenced value.
+    /// This is an associated function so that it doesn't shadow any method of `U`.
+    #[must_use]
+    pub fn map<V: ?Sized>(
+        this: Self,
+        f: impl FnOnce(&U) -> &V,
+    ) -> MappedRef<'t, T, V, MANUAL_DROP> {
+        let value = f(unsafe { &*this.value }) as *const V;
+        MappedRef { r: this.r, value }
+    }
+
+    /// Returns the strong reference to the whole item
+    #[must_use]
+    pub fn strong(this: &Self) -> &StrongRef<'t, T, MANUAL_DROP> {
+        &this.r
+    }
+
+    #[must_use]
+    pub fn into_strong(this: Self) -> StrongRef<'t, T, MANUAL_DROP> {
+        this.r
+    }
+}
+
+impl<'t, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> Deref for MappedRef<'t, T, U, MANUAL_DROP> {
+    type Target = U;
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'t, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> Clone for MappedRef<'t, T, U, MANUAL_DROP> {
+    fn clone(&self) -> Self {
+        Self {
+            r: self.r.clone(),
+            value: self.value,
+        }
+    }
+}
+
+impl<'t, T: ?Sized, U: ?Sized + core::fmt::Debug, const MANUAL_DROP: bool> core::fmt::Debug
+    for MappedRef<'t, T, U, MANUAL_DROP>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MappedRef")
+            .field("strong", &self.r)
+            .field("value", &&**self)
+            .finish()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> StrongRef<'t, T, MANUAL_DROP> {
+    /// Returns a reference to a part of the item, for example a field, which keeps the item alive.
+    /// This is an associated function so that it doesn't shadow any method of `T`.
+    #[must_use]
+    pub fn map<U: ?Sized>(
+        this: Self,
+        f: impl FnOnce(&T) -> &U,
+    ) -> MappedRef<'t, T, U, MANUAL_DROP> {
+        let value = f(&this) as *const U;
+        MappedRef { r: this, value }
+    }
+}
+
+/// A mutable reference to a part of a pool item, created with [RefMut::map].
+/// Keeps the item mutably borrowed in the same way as the [RefMut] it was created from.
+pub struct MappedRefMut<'u, 't, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> {
+    r: RefMut<'u, 't, T, MANUAL_DROP>,
+    value: *mut U,
+}
+
+impl<'u, 't, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool>
+    MappedRefMut<'u, 't, T, U, MANUAL_DROP>
+{
+    /// Maps the reference to a part of the referenced value.
+    /// This is an associated function so that it doesn't shadow any method of `U`.
+    #[must_use]
+    pub fn map<V: ?Sized>(
+        this: Self,
+        f: impl FnOnce(&mut U) -> &mut V,
+    ) -> MappedRefMut<'u, 't, T, V, MANUAL_DROP> {
+        let value = f(unsafe { &mut *this.value }) as *mut V;
+        MappedRefMut { r: this.r, value }
+    }
+
+    /// Maps a pinned reference to a part of the pinned value, see [RefMut::map_pin]
+    #[must_use]
+    pub fn map_pin<V: ?Sized>(
+        this: Pin<Self>,
+        f: impl FnOnce(Pin<&mut U>) -> Pin<&mut V>,
+    ) -> Pin<MappedRefMut<'u, 't, T, V, MANUAL_DROP>> {
+        // The value is never moved and the returned part is pinned by `f`
+        let this = unsafe { Pin::into_inner_unchecked(this) };
+        let value =
+            unsafe { Pin::get_unchecked_mut(f(Pin::new_unchecked(&mut *this.value))) } as *mut V;
+        unsafe { Pin::new_unchecked(MappedRefMut { r: this.r, value }) }
+    }
+}
+
+impl<'u, 't, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> Deref
+    for MappedRefMut<'u, 't, T, U, MANUAL_DROP>
+{
+    type Target = U;
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'u, 't, T: ?Sized, U: ?Sized, const MANUAL_DROP: bool> DerefMut
+    for MappedRefMut<'u, 't, T, U, MANUAL_DROP>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.value }
+    }
+}
+
+impl<'u, 't, T: ?Sized, const MANUAL_DROP: bool> RefMut<'u, 't, T, MANUAL_DROP> {
+    /// Returns a mutable reference to a part of the item, for example a field, which keeps the item
+    /// mutably borrowed. This is an associated function so that it doesn't shadow any method of `T`.
+    #[must_use]
+    pub fn map<U: ?Sized>(
+        mut this: Self,
+        f: impl FnOnce(&mut T) -> &mut U,
+    ) -> MappedRefMut<'u, 't, T, U, MANUAL_DROP> {
+        let value = f(&mut this) as *mut U;
+        MappedRefMut { r: this, value }
+    }
+
+    /// Returns a pinned mutable reference to a part of a pinned item, for example a structurally pinned field
+    /// (see [core::pin]), which keeps the item mutably borrowed
+    #[must_use]
+    pub fn map_pin<U: ?Sized>(
+        this: Pin<Self>,
+        f: impl FnOnce(Pin<&mut T>) -> Pin<&mut U>,
+    ) -> Pin<MappedRefMut<'u, 't, T, U, MANUAL_DROP>> {
+        // The item is never moved and the returned part is pinned by `f`
+        let mut this = unsafe { Pin::into_inner_unchecked(this) };
+        let value = unsafe { Pin::get_unchecked_mut(f(Pin::new_unchecked(&mut *this))) } as *mut U;
+        unsafe { Pin::new_unchecked(MappedRefMut { r: this, value }) }
+    }
+}
@@ -1,13 +1,43 @@
-use std::num::NonZeroUsize;
+use core::num::NonZeroUsize;
 
+pub use allocator::*;
+pub use any_pool::*;
+pub use counted_weak_ref::*;
+pub use error::*;
+pub use growth::*;
+pub use iter::*;
+pub use key::*;
+pub use mapped_ref::*;
+pub use page_view::*;
+#[cfg(feature = "rayon")]
+pub use par_iter::*;
 pub use pool::*;
+pub use pool_cell::*;
+pub use static_pool::*;
+pub use stats::*;
 pub use strong_ref::*;
+pub use unique_ref::*;
 pub use weak_ref::*;
 
+mod allocator;
+mod any_pool;
+mod counted_weak_ref;
+mod error;
+mod growth;
+mod iter;
+mod key;
+mod mapped_ref;
 mod page;
+mod page_view;
+#[cfg(feature = "rayon")]
+mod par_iter;
 mod pool;
+mod pool_cell;
 mod slot;
+mod static_pool;
+mod stats;
 mod strong_ref;
+mod unique_ref;
 mod weak_ref;
 
 pub type Index = u32;
@@ -1,93 +1,490 @@
-use super::slot::Slot;
-use crate::{CellTrait, Index, PoolHeader};
-use std::{
+use super::slot::{drop_slot_item, DropItemFn, Slot, SlotHeader};
+use crate::{CellTrait, Index, Key, PoolError, PoolHeader, Version};
+use core::{
+    alloc::Layout,
     cell::Cell,
-    mem::{ManuallyDrop, MaybeUninit},
-    num::NonZeroUsize,
-    ops::Deref,
+    marker::PhantomData,
+    mem::size_of,
+    ptr::{null, NonNull},
 };
 
-pub(crate) struct PageHeader<T> {
-    header: *const PoolHeader<T>,
-    pub(crate) next_page: Option<Box<Page<T>>>,
-    pub(crate) next_free_page: Cell<Option<*const Page<T>>>,
-    first_free_slot: Cell<Index>,
+/// Maximum size in bytes of a page, so that slot offsets fit in 31 bits, see [SlotHeader::offset]
+pub(crate) const MAX_PAGE_SIZE: usize = i32::MAX as usize;
+
+/// The list of pages with free slots, which is the first field of the pool header
+pub(crate) type FreePageList = Cell<*const PageHeader>;
+
+/// A word of the occupancy bitmap, which has one bit per slot that is set if the slot is occupied
+type OccupancyWord = Cell<usize>;
+
+const WORD_BITS: Index = usize::BITS;
+
+/// Page header, which doesn't depend on the item type so that slots can be released through references to
+/// unsized items. The type specific parts are the slot layout, described by `first_slot` and `slot_size`,
+/// and the item destructor. The occupancy bitmap is stored after the slots, at `occupancy` bytes from the header.
+pub(crate) struct PageHeader {
+    free_pages: *const FreePageList, // Null if there's no free page list
+    next_page: Cell<Option<NonNull<PageHeader>>>,
+    prev_page: Cell<Option<NonNull<PageHeader>>>,
+    pub(crate) next_free_page: Cell<*const PageHeader>, // Null if this is the last page with free slots
+    first_free_slot: Cell<u32>,                         // Offset of the first free slot header
     count: Cell<Index>,
+    borrowed: Cell<Index>,   // Number of mutably borrowed items
+    weak_refs: Cell<bool>, // Set once a WeakRef into this page has been created, keeps it from being removed
+    weak_count: Cell<usize>, // Number of counted weak references into this page, which keep it from being free'd
+    capacity: Index,
+    first_id: Index, // Pool wide id of the first slot in this page, see [crate::Key]
+    first_slot: u32, // Offset of the first slot header
+    slot_size: u32,
+    occupancy: u32,
+    drop_item: DropItemFn,
 }
 
-union SlotUnion<T> {
-    header: ManuallyDrop<PageHeader<T>>,
-    slot: ManuallyDrop<Slot<T>>,
-}
+impl PageHeader {
+    /// Returns the header of the page a slot belongs to
+    #[must_use]
+    pub(crate) unsafe fn of(slot: &SlotHeader) -> &PageHeader {
+        &*(slot as *const SlotHeader as *const u8)
+            .sub(slot.offset() as usize)
+            .cast::<PageHeader>()
+    }
 
-pub(crate) struct Page<T> {
-    slots: Vec<SlotUnion<T>>,
-}
+    #[must_use]
+    fn index(&self, slot: &SlotHeader) -> Index {
+        (slot.offset() - self.first_slot) / self.slot_size
+    }
 
-impl<T> Page<T> {
+    /// Returns the pool wide id of a slot in this page
     #[must_use]
-    pub fn new(header: *const PoolHeader<T>, cap: Index, next_page: Option<Box<Page<T>>>) -> Self {
-        let mut slots = Vec::with_capacity(cap as usize + 1);
-
-        slots.push(SlotUnion {
-            header: ManuallyDrop::new(PageHeader {
-                header,
-                next_page,
-                next_free_page: Default::default(),
-                first_free_slot: Default::default(),
-                count: Default::default(),
-            }),
-        });
+    pub(crate) fn slot_id(&self, slot: &SlotHeader) -> Index {
+        self.first_id + self.index(slot)
+    }
 
-        for i in 1..slots.capacity() {
-            slots.push(SlotUnion {
-                slot: ManuallyDrop::new(Slot {
-                    item: MaybeUninit::uninit().into(),
-                    version: Cell::new(NonZeroUsize::new(1).unwrap()),
-                    count: 0.into(),
-                    index: (i as Index).into(),
-                }),
-            })
-        }
+    #[must_use]
+    pub(crate) fn len(&self) -> Index {
+        self.count.get()
+    }
+
+    /// Returns the number of mutably borrowed items
+    #[must_use]
+    pub(crate) fn borrowed(&self) -> &Cell<Index> {
+        &self.borrowed
+    }
+
+    /// Returns whether a [crate::WeakRef] to a slot in this page has been created. Since they aren't counted,
+    /// such a page can't be removed by [crate::RcPool::shrink_to_fit].
+    #[must_use]
+    pub(crate) fn weak_refs(&self) -> &Cell<bool> {
+        &self.weak_refs
+    }
 
-        Self { slots }
+    /// Returns the number of [crate::CountedWeakRef]s to slots in this page
+    #[must_use]
+    pub(crate) fn weak_count(&self) -> &Cell<usize> {
+        &self.weak_count
     }
 
     #[must_use]
-    pub(crate) fn header(&self) -> &PageHeader<T> {
-        unsafe { self.slots.get_unchecked(0).header.deref() }
+    pub(crate) fn next_page(&self) -> Option<&PageHeader> {
+        self.next_page.get().map(|p| unsafe { &*p.as_ptr() })
     }
 
     #[must_use]
-    pub(crate) unsafe fn get(&self, index: Index) -> Option<&Slot<T>> {
-        let slot = self.slots.get_unchecked(index as usize + 1);
+    pub(crate) fn prev_page(&self) -> Option<&PageHeader> {
+        self.prev_page.get().map(|p| unsafe { &*p.as_ptr() })
+    }
 
-        if slot.slot.is_free() {
-            None
+    #[must_use]
+    pub(crate) fn capacity(&self) -> Index {
+        self.capacity
+    }
+
+    #[must_use]
+    pub(crate) fn first_id(&self) -> Index {
+        self.first_id
+    }
+
+    #[must_use]
+    pub(crate) fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Number of bytes allocated for this page
+    #[must_use]
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.occupancy as usize + Self::occupancy_layout(self.capacity).unwrap().size()
+    }
+
+    /// Layout of the occupancy bitmap of a page with `cap` slots
+    #[must_use]
+    pub(crate) fn occupancy_layout(cap: Index) -> Option<Layout> {
+        Layout::array::<OccupancyWord>(cap.div_ceil(WORD_BITS) as usize).ok()
+    }
+
+    #[must_use]
+    fn occupancy(&self) -> &[OccupancyWord] {
+        unsafe {
+            core::slice::from_raw_parts(
+                (self as *const Self as *const u8)
+                    .add(self.occupancy as usize)
+                    .cast(),
+                self.capacity.div_ceil(WORD_BITS) as usize,
+            )
+        }
+    }
+
+    /// Returns the occupancy bitmap, which must not change while the returned slice is alive
+    #[must_use]
+    pub(crate) unsafe fn occupancy_bits(&self) -> &[usize] {
+        let occupancy = self.occupancy();
+        core::slice::from_raw_parts(occupancy.as_ptr().cast(), occupancy.len())
+    }
+
+    #[must_use]
+    pub(crate) fn is_occupied(&self, index: Index) -> bool {
+        index < self.capacity
+            && self.occupancy()[(index / WORD_BITS) as usize].get() & (1 << (index % WORD_BITS))
+                != 0
+    }
+
+    fn set_occupied(&self, index: Index, occupied: bool) {
+        let word = &self.occupancy()[(index / WORD_BITS) as usize];
+        let bit = 1 << (index % WORD_BITS);
+        word.set(if occupied {
+            word.get() | bit
         } else {
-            Some(&slot.slot)
+            word.get() & !bit
+        });
+    }
+
+    /// Returns the index of the first occupied slot in `start..end`, skipping free slots a word at a time
+    #[must_use]
+    pub(crate) fn next_occupied(&self, start: Index, end: Index) -> Option<Index> {
+        let occupancy = self.occupancy();
+        let mut i = start;
+
+        while i < end {
+            let word = occupancy[(i / WORD_BITS) as usize].get() >> (i % WORD_BITS);
+
+            if word != 0 {
+                let index = i + word.trailing_zeros();
+                return (index < end).then_some(index);
+            }
+
+            i = (i / WORD_BITS + 1) * WORD_BITS;
         }
+
+        None
     }
 
+    /// Returns the index of the last occupied slot in `start..end`, skipping free slots a word at a time
     #[must_use]
-    pub(crate) fn len(&self) -> Index {
-        self.header().count.get()
+    pub(crate) fn prev_occupied(&self, start: Index, end: Index) -> Option<Index> {
+        let occupancy = self.occupancy();
+        let mut i = end;
+
+        while i > start {
+            let last = i - 1;
+            let word =
+                occupancy[(last / WORD_BITS) as usize].get() << (WORD_BITS - 1 - last % WORD_BITS);
+
+            if word != 0 {
+                let index = last - word.leading_zeros();
+                return (index >= start).then_some(index);
+            }
+
+            i = last / WORD_BITS * WORD_BITS;
+        }
+
+        None
     }
 
     #[must_use]
-    pub(crate) fn capacity(&self) -> Index {
-        self.slots.len() as Index - 1
+    fn slot_at(&self, offset: u32) -> &SlotHeader {
+        unsafe {
+            &*(self as *const Self as *const u8)
+                .add(offset as usize)
+                .cast::<SlotHeader>()
+        }
+    }
+
+    #[must_use]
+    pub(crate) unsafe fn slot(&self, index: Index) -> &SlotHeader {
+        self.slot_at(self.first_slot + index * self.slot_size)
     }
 
+    /// Moves the value into the first free slot of this page, which must not be full and have items of type `T`
     #[must_use]
-    pub(crate) unsafe fn insert(&self, value: T) -> &Slot<T> {
-        let header = self.header();
-        let index = header.first_free_slot.get() + 1;
-        let slot = &self.slots.get_unchecked(index as usize).slot;
+    pub(crate) unsafe fn insert<T>(&self, value: T) -> &SlotHeader {
+        debug_assert!(!self.is_full());
+        let slot = self.slot_at(self.first_free_slot.get());
+        self.first_free_slot.set(slot.count.get());
+        slot.count.set(0);
         slot.set_value(value);
-        header.first_free_slot.set(slot.index.get());
-        header.count.add(1);
-        slot.index.set(index - 1);
+        self.set_occupied(self.index(slot), true);
+        self.count.add(1);
         slot
     }
+
+    /// Puts a slot, which must belong to this page and whose item has already been taken,
+    /// first in the free slot list. If the page was full, it's put first in the pool's free page list.
+    pub(crate) fn release(&self, slot: &SlotHeader) {
+        let was_full = self.is_full();
+        slot.count.set(self.first_free_slot.get());
+        self.first_free_slot.set(slot.offset());
+        self.set_occupied(self.index(slot), false);
+        self.count.sub(1);
+
+        if was_full {
+            if let Some(free_pages) = unsafe { self.free_pages.as_ref() } {
+                self.next_free_page.set(free_pages.get());
+                free_pages.set(self as *const _);
+            }
+        }
+    }
+
+    /// Drops the item of a slot in this page with the destructor of the page's item type,
+    /// without releasing the slot
+    pub(crate) unsafe fn drop_item(&self, slot: &SlotHeader) {
+        (self.drop_item)(NonNull::from(slot))
+    }
+
+    /// Returns the highest version of any slot in this page
+    #[must_use]
+    pub(crate) fn max_version(&self) -> Version {
+        (0..self.capacity)
+            .map(|i| unsafe { self.slot(i) }.version.get())
+            .max()
+            .unwrap_or(Version::MIN)
+    }
+
+    /// Returns the slot identified by the key, if it's in this page and still contains the same item
+    #[must_use]
+    pub(crate) fn find(&self, key: Key) -> Option<&SlotHeader> {
+        let index = key.id().checked_sub(self.first_id)?;
+
+        if index >= self.capacity {
+            return None;
+        }
+
+        let slot = unsafe { self.slot(index) };
+        key.matches(slot).then_some(slot)
+    }
+
+    /// Drops all live items in slot order, without releasing their slots
+    pub(crate) unsafe fn drop_items(&self) {
+        let mut start = 0;
+
+        while let Some(i) = self.next_occupied(start, self.capacity) {
+            self.drop_item(self.slot(i));
+            start = i + 1;
+        }
+    }
+
+    /// Writes a page header, `cap` free slots for items of type `T` and the occupancy bitmap to `page`,
+    /// at the offsets given by `layout`. `free_pages` is the pool's free page list, it may be null
+    /// if there's no such list. `first_id` is the pool wide id of the first slot and `version` the initial
+    /// version of all slots, which must be odd.
+    pub(crate) unsafe fn init<T>(
+        page: NonNull<u8>,
+        layout: &PageLayout,
+        free_pages: *const FreePageList,
+        cap: Index,
+        first_id: Index,
+        version: Version,
+    ) {
+        Self::init_raw(
+            page,
+            free_pages,
+            cap,
+            first_id,
+            version,
+            (layout.first_slot + Slot::<T>::HEADER_OFFSET) as u32,
+            size_of::<Slot<T>>() as u32,
+            layout.occupancy as u32,
+            drop_slot_item::<T>,
+        )
+    }
+
+    /// Writes a page header followed by `cap` free slots to `page`. The slot headers are placed at
+    /// `first_slot + i * slot_size` bytes from the page header, the occupancy bitmap at `occupancy` bytes
+    /// (see [PageHeader::occupancy_layout]) and `drop_item` drops the item of an occupied slot.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) unsafe fn init_raw(
+        page: NonNull<u8>,
+        free_pages: *const FreePageList,
+        cap: Index,
+        first_id: Index,
+        version: Version,
+        first_slot: u32,
+        slot_size: u32,
+        occupancy: u32,
+        drop_item: DropItemFn,
+    ) {
+        page.cast::<PageHeader>().as_ptr().write(PageHeader {
+            free_pages,
+            next_page: Default::default(),
+            prev_page: Default::default(),
+            next_free_page: Cell::new(null()),
+            first_free_slot: Cell::new(first_slot),
+            count: Default::default(),
+            borrowed: Default::default(),
+            weak_refs: Default::default(),
+            weak_count: Default::default(),
+            capacity: cap,
+            first_id,
+            first_slot,
+            slot_size,
+            occupancy,
+            drop_item,
+        });
+
+        let words = page
+            .as_ptr()
+            .add(occupancy as usize)
+            .cast::<OccupancyWord>();
+
+        for i in 0..cap.div_ceil(WORD_BITS) {
+            words.add(i as usize).write(Cell::new(0));
+        }
+
+        for i in 0..cap {
+            let offset = first_slot + i * slot_size;
+            let slot = page.as_ptr().add(offset as usize).cast::<SlotHeader>();
+            slot.write(SlotHeader::new(offset, offset + slot_size, version));
+        }
+    }
+}
+
+/// Memory layout of a page: the page header, padded to the slot alignment, followed by the slots and
+/// the occupancy bitmap
+pub(crate) struct PageLayout {
+    pub(crate) layout: Layout,
+    pub(crate) first_slot: usize, // Offset of the first slot
+    pub(crate) occupancy: usize,  // Offset of the occupancy bitmap
+}
+
+impl PageLayout {
+    /// Returns the layout of a page with `cap` slots for items of type `T`, or None if it's too large
+    /// for the slot offsets
+    #[must_use]
+    pub(crate) fn of<T>(cap: Index) -> Option<Self> {
+        let slots = Layout::array::<Slot<T>>(cap as usize).ok()?;
+        let (layout, first_slot) = Layout::new::<PageHeader>().extend(slots).ok()?;
+        let (layout, occupancy) = layout.extend(PageHeader::occupancy_layout(cap)?).ok()?;
+
+        (layout.size() <= MAX_PAGE_SIZE).then_some(Self {
+            layout,
+            first_slot,
+            occupancy,
+        })
+    }
+}
+
+/// An owned page allocated from the pool's [crate::PageAllocator], see [PageLayout]
+pub(crate) struct Page<T> {
+    header: NonNull<PageHeader>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Page<T> {
+    /// Allocates a new page whose slots start at the given id and version. Fails if the page is too large
+    /// for the slot offsets or if the allocator is out of memory.
+    pub fn new(
+        pool: *const PoolHeader<T>,
+        cap: Index,
+        first_id: Index,
+        version: Version,
+    ) -> Result<Self, PoolError> {
+        let layout = PageLayout::of::<T>(cap).ok_or(PoolError::PageTooLarge)?;
+
+        let page =
+            unsafe { (*pool).allocator.allocate(layout.layout) }.ok_or(PoolError::OutOfMemory)?;
+
+        let free_pages = PoolHeader::free_pages(pool);
+        unsafe { PageHeader::init::<T>(page, &layout, free_pages, cap, first_id, version) };
+        Ok(unsafe { Self::from_header(page.cast()) })
+    }
+
+    /// Takes ownership of a page that was linked to another page's header
+    #[must_use]
+    unsafe fn from_header(header: NonNull<PageHeader>) -> Self {
+        Self {
+            header,
+            phantom: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn header(&self) -> &PageHeader {
+        unsafe { self.header.as_ref() }
+    }
+
+    /// Unlinks and returns the next page in the chain, so that pages can be dropped one at a time
+    #[must_use]
+    pub(crate) fn take_next_page(&mut self) -> Option<Page<T>> {
+        let next = unsafe { Self::from_header(self.header().next_page.take()?) };
+        next.header().prev_page.set(None);
+        Some(next)
+    }
+
+    pub(crate) fn set_next_page(&mut self, next_page: Option<Page<T>>) {
+        let header = NonNull::from(self.header());
+
+        self.header().next_page.set(next_page.map(|p| {
+            p.header().prev_page.set(Some(header));
+            let next = NonNull::from(p.header());
+            core::mem::forget(p);
+            next
+        }))
+    }
+
+    #[must_use]
+    pub(crate) fn len(&self) -> Index {
+        self.header().len()
+    }
+
+    /// Size of one slot
+    pub(crate) const SLOT_SIZE: usize = size_of::<Slot<T>>();
+}
+
+/// Iterates over a page and all pages after it in the page list
+pub(crate) struct PageIterator<'t> {
+    page: Option<NonNull<PageHeader>>,
+    phantom: PhantomData<&'t PageHeader>,
+}
+
+impl<'t> PageIterator<'t> {
+    #[must_use]
+    pub(crate) fn new(page: Option<&'t PageHeader>) -> Self {
+        Self {
+            page: page.map(NonNull::from),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'t> Iterator for PageIterator<'t> {
+    type Item = &'t PageHeader;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let page = unsafe { self.page?.as_ref() };
+        self.page = page.next_page.get();
+        Some(page)
+    }
+}
+
+impl<T> Drop for Page<T> {
+    /// Drops all live items in slot order and returns the page memory to the allocator.
+    /// The next page is not dropped here, see [Page::take_next_page]
+    fn drop(&mut self) {
+        unsafe {
+            self.header().drop_items();
+            let pool = self.header().free_pages as *const PoolHeader<T>;
+            let layout = PageLayout::of::<T>(self.header().capacity()).unwrap();
+            (*pool)
+                .allocator
+                .deallocate(self.header.cast(), layout.layout)
+        }
+    }
 }
@@ -0,0 +1,223 @@
+use super::page::{PageHeader, PageIterator};
+use crate::{Index, RcPool, ValueIterator, ValueMutIterator};
+use core::{marker::PhantomData, ops::Range};
+
+/// Shared access to the items of one pool page, see [RcPool::page_views]. Slot `i` of the page has the pool wide
+/// slot id `first_id() + i`, which is the id used in [crate::Key]s.
+pub struct PageView<'t, T> {
+    page: &'t PageHeader,
+    phantom: PhantomData<&'t T>,
+}
+
+impl<'t, T> PageView<'t, T> {
+    /// Returns the pool wide slot id of the first slot in the page
+    #[must_use]
+    pub fn first_id(&self) -> Index {
+        self.page.first_id()
+    }
+
+    /// Returns the pool wide slot ids of all slots in the page
+    #[must_use]
+    pub fn slot_ids(&self) -> Range<Index> {
+        self.first_id()..self.first_id() + self.capacity()
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> Index {
+        self.page.capacity()
+    }
+
+    /// Returns the number of live items in the page
+    #[must_use]
+    pub fn len(&self) -> Index {
+        self.page.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the slot at the page index contains an item
+    #[must_use]
+    pub fn is_occupied(&self, index: Index) -> bool {
+        self.page.is_occupied(index)
+    }
+
+    /// Returns the occupancy bitmap of the page, where bit `i % usize::BITS` of word `i / usize::BITS`
+    /// is set if slot `i` contains an item
+    #[must_use]
+    pub fn occupancy(&self) -> &'t [usize] {
+        // The pool is mutably borrowed, so no items can be inserted or removed while the view exists
+        unsafe { self.page.occupancy_bits() }
+    }
+
+    /// Returns the item in the slot at the page index, if it's occupied
+    #[must_use]
+    pub fn get(&self, index: Index) -> Option<&'t T> {
+        self.is_occupied(index)
+            .then(|| unsafe { self.page.slot(index).item().as_ref() })
+    }
+
+    /// Returns an iterator over the items of the page in slot order
+    #[must_use]
+    pub fn iter(&self) -> ValueIterator<'t, T> {
+        ValueIterator::page(self.page)
+    }
+}
+
+impl<'t, T> Clone for PageView<'t, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'t, T> Copy for PageView<'t, T> {}
+
+impl<'t, T: 't> IntoIterator for PageView<'t, T> {
+    type Item = &'t T;
+    type IntoIter = ValueIterator<'t, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable access to the items of one pool page, see [RcPool::page_views_mut]. Pinned items are only
+/// accessible as shared references.
+pub struct PageViewMut<'t, T> {
+    page: &'t PageHeader,
+    phantom: PhantomData<&'t mut T>,
+}
+
+// A mutable page view is the only access to its page while the pool is mutably borrowed, so it can be sent to
+// another thread like a `&mut [T]`. Items can't contain strong references into the pool since those aren't Send.
+unsafe impl<'t, T: Send> Send for PageViewMut<'t, T> {}
+
+impl<'t, T> PageViewMut<'t, T> {
+    /// Returns the pool wide slot id of the first slot in the page
+    #[must_use]
+    pub fn first_id(&self) -> Index {
+        self.page.first_id()
+    }
+
+    /// Returns the pool wide slot ids of all slots in the page
+    #[must_use]
+    pub fn slot_ids(&self) -> Range<Index> {
+        self.first_id()..self.first_id() + self.capacity()
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> Index {
+        self.page.capacity()
+    }
+
+    /// Returns the number of live items in the page
+    #[must_use]
+    pub fn len(&self) -> Index {
+        self.page.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the slot at the page index contains an item
+    #[must_use]
+    pub fn is_occupied(&self, index: Index) -> bool {
+        self.page.is_occupied(index)
+    }
+
+    /// Returns the occupancy bitmap of the page, see [PageView::occupancy]
+    #[must_use]
+    pub fn occupancy(&self) -> &[usize] {
+        unsafe { self.page.occupancy_bits() }
+    }
+
+    /// Returns the item in the slot at the page index, if it's occupied
+    #[must_use]
+    pub fn get(&self, index: Index) -> Option<&T> {
+        self.is_occupied(index)
+            .then(|| unsafe { self.page.slot(index).item().as_ref() })
+    }
+
+    /// Returns the item in the slot at the page index, if it's occupied and not pinned
+    #[must_use]
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        if !self.is_occupied(index) {
+            return None;
+        }
+
+        let slot = unsafe { self.page.slot(index) };
+        (!slot.is_pinned()).then(|| unsafe { slot.item().as_mut() })
+    }
+
+    /// Returns an iterator over the items of the page that aren't pinned, in slot order
+    #[must_use]
+    pub fn iter_mut(&mut self) -> ValueMutIterator<'_, T> {
+        ValueMutIterator::page(self.page)
+    }
+}
+
+impl<'t, T: 't> IntoIterator for PageViewMut<'t, T> {
+    type Item = &'t mut T;
+    type IntoIter = ValueMutIterator<'t, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ValueMutIterator::page(self.page)
+    }
+}
+
+impl<T, const MANUAL_DROP: bool> RcPool<T, MANUAL_DROP> {
+    /// Returns an iterator over views of all pages, in the same order as [RcPool::iter]. Like [RcPool::values]
+    /// it requires a mutable pool reference, so that no item can be mutably borrowed while it's referenced.
+    #[must_use]
+    pub fn page_views(&mut self) -> PageViewIterator<'_, T> {
+        PageViewIterator {
+            pages: self.pages(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over mutable views of all pages, in the same order as [RcPool::iter]
+    #[must_use]
+    pub fn page_views_mut(&mut self) -> PageViewMutIterator<'_, T> {
+        PageViewMutIterator {
+            pages: self.pages(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+pub struct PageViewIterator<'t, T> {
+    pages: PageIterator<'t>,
+    phantom: PhantomData<&'t T>,
+}
+
+impl<'t, T> Iterator for PageViewIterator<'t, T> {
+    type Item = PageView<'t, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pages.next().map(|page| PageView {
+            page,
+            phantom: PhantomData,
+        })
+    }
+}
+
+pub struct PageViewMutIterator<'t, T> {
+    pages: PageIterator<'t>,
+    phantom: PhantomData<&'t mut T>,
+}
+
+impl<'t, T> Iterator for PageViewMutIterator<'t, T> {
+    type Item = PageViewMut<'t, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pages.next().map(|page| PageViewMut {
+            page,
+            phantom: PhantomData,
+        })
+    }
+}
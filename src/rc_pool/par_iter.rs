@@ -0,0 +1,38 @@
+use crate::{PageViewMut, RcPool};
+use alloc::vec::Vec;
+use rayon::iter::{plumbing::UnindexedConsumer, IntoParallelIterator, ParallelIterator};
+
+impl<T: Send, const MANUAL_DROP: bool> RcPool<T, MANUAL_DROP> {
+    /// Returns a parallel iterator over mutable references to all items, except pinned items. The work is split
+    /// between threads at page granularity, so the pages should be large enough to amortize the scheduling.
+    /// Requires the `rayon` feature.
+    #[must_use]
+    pub fn par_iter_mut(&mut self) -> ParValueMutIterator<'_, T> {
+        ParValueMutIterator {
+            pages: self.page_views_mut().collect(),
+        }
+    }
+
+    /// Calls `f` with each item that isn't pinned, in parallel, see [RcPool::par_iter_mut].
+    /// Requires the `rayon` feature.
+    pub fn par_for_each(&mut self, f: impl Fn(&mut T) + Sync + Send) {
+        self.par_iter_mut().for_each(f)
+    }
+}
+
+/// Iterates in parallel over mutable references to all items of a pool that aren't pinned,
+/// see [RcPool::par_iter_mut]
+pub struct ParValueMutIterator<'t, T> {
+    pages: Vec<PageViewMut<'t, T>>,
+}
+
+impl<'t, T: Send + 't> ParallelIterator for ParValueMutIterator<'t, T> {
+    type Item = &'t mut T;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        self.pages
+            .into_par_iter()
+            .flat_map_iter(PageViewMut::into_iter)
+            .drive_unindexed(consumer)
+    }
+}
@@ -1,169 +1,613 @@
-use super::page::Page;
-use crate::{Either, Index, StrongRef};
-use std::marker::PhantomData;
-use std::ptr::null;
-use std::{
-    cell::{Cell, UnsafeCell},
-    ops::Deref,
+use super::page::{FreePageList, Page, PageHeader, PageIterator, PageLayout};
+use crate::{
+    CountedWeakRef, DrainIterator, Either, FixedGrowth, GlobalPageAllocator, GrowthContext,
+    GrowthPolicy, Index, Key, PageAllocator, Pool, PoolError, PoolFull, PoolLimits, RcPoolIterator,
+    StrongRef, UniqueRef, ValueIterator, ValueMutIterator, Version, WeakRef, WeakRefIterator,
 };
+use alloc::{boxed::Box, vec::Vec};
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::pin::Pin;
+use core::ptr::{addr_of, null};
 
+/// A range of slot ids which aren't used by any page. A page re-using the ids must start its slot versions
+/// at `version`, so that keys to items in the page that used the ids before don't match the new items.
+struct IdRange {
+    start: Index,
+    len: Index,
+    version: Version, // Highest slot version of the pages that used the ids before
+}
+
+impl IdRange {
+    #[must_use]
+    fn end(&self) -> Index {
+        self.start + self.len
+    }
+}
+
+#[repr(C)] // The free page list is the first field, so that pages can find the pool header from it
 pub(crate) struct PoolHeader<T> {
-    first_page: UnsafeCell<Option<Box<Page<T>>>>,
-    first_free_page: Cell<*const Page<T>>,
+    first_free_page: FreePageList, // Null if all pages are full
+    first_page: UnsafeCell<Option<Page<T>>>,
+    pages: RefCell<Vec<*const PageHeader>>, // All pages ordered by their first slot id
+    free_ids: RefCell<Vec<IdRange>>,        // Ids of removed pages, ordered by id
+    next_id: Cell<Index>,                   // First slot id which has never been used
+    retired: RefCell<Vec<Page<T>>>, // Empty pages removed by shrink_to_fit, which weak references point into
+    iterators: Cell<usize>, // Number of live iterators, which keep shrink_to_fit from unlinking pages
+    pub(crate) allocator: Box<dyn PageAllocator>,
 }
 
 impl<T> PoolHeader<T> {
-    unsafe fn first_page(&self) -> &Page<T> {
-        (*self.first_page.get()).as_ref().unwrap()
+    /// Returns a pointer to the free page list of a pool header, which can be cast back to the pool header
+    #[must_use]
+    pub(crate) fn free_pages(pool: *const Self) -> *const FreePageList {
+        unsafe { addr_of!((*pool).first_free_page) }
+    }
+
+    fn first_free_page(&self) -> Option<&PageHeader> {
+        unsafe { self.first_free_page.get().as_ref() }
+    }
+
+    /// Puts a page, which must not already be in the list, first in the list of pages with free slots
+    fn push_free_page(&self, page: &PageHeader) {
+        page.next_free_page.set(self.first_free_page.get());
+        self.first_free_page.set(page as *const _);
+    }
+
+    fn pop_free_page(&self) {
+        if let Some(page) = self.first_free_page() {
+            self.first_free_page.set(page.next_free_page.get());
+            page.next_free_page.set(null());
+        }
+    }
+
+    /// Takes `len` consecutive slot ids for a new page, re-using the ids of removed pages if possible.
+    /// Returns the first id and the initial slot version, or None if the slot ids have run out.
+    #[must_use]
+    fn take_ids(&self, len: Index) -> Option<(Index, Version)> {
+        let mut free_ids = self.free_ids.borrow_mut();
+
+        if let Some(i) = free_ids.iter().position(|r| r.len >= len) {
+            let range = &mut free_ids[i];
+            let ids = (range.start, range.version);
+            range.start += len;
+            range.len -= len;
+
+            if range.len == 0 {
+                free_ids.remove(i);
+            }
+
+            return Some(ids);
+        }
+
+        // Ids which have never been used can be added to the end of the last free range
+        let last = free_ids.last().filter(|r| r.end() == self.next_id.get());
+        let (start, version) =
+            last.map_or((self.next_id.get(), Version::MIN), |r| (r.start, r.version));
+        self.next_id.set(start.checked_add(len)?);
+
+        if last.is_some() {
+            free_ids.pop();
+        }
+
+        Some((start, version))
+    }
+
+    /// Makes the slot ids of a removed page available for new pages
+    fn release_ids(&self, start: Index, len: Index, version: Version) {
+        let mut free_ids = self.free_ids.borrow_mut();
+        let i = free_ids.partition_point(|r| r.start < start);
+        free_ids.insert(
+            i,
+            IdRange {
+                start,
+                len,
+                version,
+            },
+        );
+
+        // Merges the range with adjacent ranges, whose ids then start at the highest version of both
+        if i + 1 < free_ids.len() && free_ids[i].end() == free_ids[i + 1].start {
+            let next = free_ids.remove(i + 1);
+            free_ids[i].len += next.len;
+            free_ids[i].version = free_ids[i].version.max(next.version);
+        }
+
+        if i > 0 && free_ids[i - 1].end() == free_ids[i].start {
+            let range = free_ids.remove(i);
+            free_ids[i - 1].len += range.len;
+            free_ids[i - 1].version = free_ids[i - 1].version.max(range.version);
+        }
+    }
+
+    /// Adds a new page first in the page list, and also in the free page list if it has any slots.
+    /// Fails if the slot ids have run out, if the page is too large or if the page allocator is out of memory.
+    fn add_page(&self, page_len: Index) -> Result<(), PoolError> {
+        let (first_id, version) = self.take_ids(page_len).ok_or(PoolError::CapacityExceeded)?;
+
+        let mut new_page = match Page::new(self as *const _, page_len, first_id, version) {
+            Ok(page) => page,
+
+            Err(e) => {
+                self.release_ids(first_id, page_len, version);
+                return Err(e);
+            }
+        };
+
+        unsafe {
+            new_page.set_next_page((*self.first_page.get()).take());
+            *self.first_page.get() = Some(new_page);
+            let page = (*self.first_page.get()).as_ref().unwrap().header();
+            let mut pages = self.pages.borrow_mut();
+            let i = pages.partition_point(|&p| (*p).first_id() < first_id);
+            pages.insert(i, page as *const _);
+
+            if !page.is_full() {
+                self.push_free_page(page);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub(crate) fn iterators(&self) -> &Cell<usize> {
+        &self.iterators
     }
 
-    unsafe fn first_free_page(&self) -> &Page<T> {
-        &*self.first_free_page.get()
+    /// Returns the number of bytes allocated for retired pages, see [RcPool::shrink_to_fit]
+    #[must_use]
+    pub(crate) fn retired_bytes(&self) -> usize {
+        self.retired
+            .borrow()
+            .iter()
+            .map(|p| p.header().allocated_bytes())
+            .sum()
+    }
+
+    /// Frees the retired pages which no weak references point into anymore
+    fn free_retired_pages(&self) {
+        self.retired
+            .borrow_mut()
+            .retain(|p| p.header().weak_count().get() > 0);
+    }
+
+    /// Returns the page which may contain the slot with the given id
+    #[must_use]
+    fn find_page(&self, id: Index) -> Option<&PageHeader> {
+        let pages = self.pages.borrow();
+        let i = pages.partition_point(|&p| unsafe { (*p).first_id() } <= id);
+        Some(unsafe { &**pages.get(i.checked_sub(1)?)? })
     }
 }
 
 pub struct RcPool<T, const MANUAL_DROP: bool> {
     header: Box<PoolHeader<T>>,
-    page_len: Cell<Index>,
+    growth_policy: RefCell<Box<dyn GrowthPolicy>>,
+    limits: Cell<PoolLimits>,
 }
 
 impl<T, const MANUAL_DROP: bool> RcPool<T, MANUAL_DROP> {
     #[must_use]
     pub fn new(page_len: Index) -> Self {
+        Self::new_in(page_len, GlobalPageAllocator)
+    }
+
+    /// Creates a pool which allocates its pages from the given allocator.
+    /// Panics if the first page can't be allocated.
+    #[must_use]
+    pub fn new_in(page_len: Index, allocator: impl PageAllocator + 'static) -> Self {
         let header: Box<PoolHeader<T>> = Box::new(PoolHeader {
             first_page: UnsafeCell::new(None),
             first_free_page: Cell::new(null()),
+            pages: Default::default(),
+            free_ids: Default::default(),
+            next_id: Default::default(),
+            retired: Default::default(),
+            iterators: Default::default(),
+            allocator: Box::new(allocator),
         });
 
-        let first_page = Box::new(Page::new(header.deref() as *const _, page_len, None));
-
-        unsafe {
-            *header.first_page.get() = Some(first_page);
-
-            header
-                .first_free_page
-                .set(header.first_page().deref() as *const _);
+        if let Err(e) = header.add_page(page_len) {
+            panic!("{e}")
         }
 
         Self {
             header,
-            page_len: page_len.into(),
+            growth_policy: RefCell::new(Box::new(FixedGrowth(page_len))),
+            limits: Default::default(),
         }
     }
 
-    /// Sets the number of slots of newly created pages
+    /// Creates a pool which can hold `capacity` items without adding any pages.
+    /// New pages will have `capacity` slots as well.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let pool = Self::new(capacity.min(Index::MAX as usize) as Index);
+        pool.reserve_exact(capacity);
+        pool
+    }
+
+    /// Sets the number of slots of newly created pages, replacing the current growth policy with [FixedGrowth]
     pub fn set_page_len(&self, page_size: Index) {
-        self.page_len.set(page_size)
+        self.set_growth_policy(FixedGrowth(page_size))
     }
 
-    /// Returns the number of slots for newly created pages
+    /// Returns the number of slots for the next created page, as decided by the growth policy
     #[must_use]
     pub fn page_len(&self) -> Index {
-        self.page_len.get()
+        let context = GrowthContext {
+            capacity: self.capacity(),
+            page_count: self.page_count(),
+            last_page_len: self.first_page().header().capacity(),
+            slot_size: Page::<T>::SLOT_SIZE,
+            header_size: PageLayout::of::<T>(0).unwrap().first_slot,
+        };
+
+        self.growth_policy.borrow().next_page_len(&context).max(1)
     }
 
-    fn add_page(&self, page_size: Index) -> &Page<T> {
-        unsafe {
-            let first_page = (*self.header.first_page.get()).take();
+    /// Sets the policy deciding the number of slots of newly created pages
+    pub fn set_growth_policy(&self, policy: impl GrowthPolicy + 'static) {
+        *self.growth_policy.borrow_mut() = Box::new(policy);
+    }
+
+    #[must_use]
+    pub fn limits(&self) -> PoolLimits {
+        self.limits.get()
+    }
+
+    /// Sets the limits for how much the pool may grow. Existing pages are not affected.
+    pub fn set_limits(&self, limits: PoolLimits) {
+        self.limits.set(limits)
+    }
+
+    /// Returns the largest number of slots, at most `page_len`, a new page can have without exceeding the limits
+    fn limit_page_len(&self, page_len: Index) -> Index {
+        let limits = self.limits();
+
+        if limits.reached(self.page_count(), self.capacity()).is_some() {
+            return 0;
+        }
+
+        match limits.max_capacity {
+            Some(max_capacity) => {
+                let free = max_capacity.saturating_sub(self.capacity());
+                page_len.min(free.min(Index::MAX as usize) as Index)
+            }
+
+            None => page_len,
+        }
+    }
+
+    /// Adds a new page with [RcPool::page_len] slots, or less if needed to stay within the limits.
+    /// Fails if no page could be added without exceeding the limits or if the allocator is out of memory.
+    fn grow(&self) -> Result<(), PoolError> {
+        match self.limit_page_len(self.page_len()) {
+            0 => Err(PoolError::CapacityExceeded),
+            page_len => self.header.add_page(page_len),
+        }
+    }
+
+    /// Makes sure that at least `additional` more items can be inserted without adding any pages,
+    /// by adding pages as decided by the growth policy.
+    /// Panics if the pool limits doesn't allow adding the pages.
+    pub fn reserve(&self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("{e}")
+        }
+    }
+
+    /// Same as [RcPool::reserve], but returns an error instead of panicking.
+    /// Pages added before the error occurred are kept.
+    pub fn try_reserve(&self, additional: usize) -> Result<(), PoolError> {
+        while self.free_slots() < additional {
+            self.grow()?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure that at least `additional` more items can be inserted without adding any pages,
+    /// by adding a page with exactly the number of missing free slots (ignoring the growth policy).
+    /// Panics if the pool limits doesn't allow adding the page.
+    pub fn reserve_exact(&self, additional: usize) {
+        if let Err(e) = self.try_reserve_exact(additional) {
+            panic!("{e}")
+        }
+    }
 
-            let new_page = Box::new(Page::new(
-                self.header.deref() as *const _,
-                page_size,
-                first_page,
-            ));
+    /// Same as [RcPool::reserve_exact], but returns an error instead of panicking.
+    /// Pages added before the error occurred are kept.
+    pub fn try_reserve_exact(&self, additional: usize) -> Result<(), PoolError> {
+        let mut missing = additional.saturating_sub(self.free_slots());
 
-            self.header
-                .first_free_page
-                .set(new_page.deref() as *const _);
+        while missing > 0 {
+            let page_len = missing.min(Index::MAX as usize) as Index;
 
-            *self.header.first_page.get() = Some(new_page);
-            self.header.first_page()
+            if self.limit_page_len(page_len) != page_len {
+                return Err(PoolError::CapacityExceeded);
+            }
+
+            self.header.add_page(page_len)?;
+            missing -= page_len as usize;
         }
+
+        Ok(())
     }
 
     /// Inserts a new item into the pool
     /// If there is a free slot, creates and returns a strong reference to that slot,
     /// otherwise returns the item
     #[must_use]
-    pub fn try_insert(&self, value: T) -> Either<StrongRef<T, MANUAL_DROP>, T> {
-        let mut page = unsafe { self.header.first_free_page() };
+    pub fn try_insert(&self, value: T) -> Either<StrongRef<'_, T, MANUAL_DROP>, T> {
+        let Some(page) = self.header.first_free_page() else {
+            return Either::Right(value);
+        };
 
-        loop {
-            if page.len() < page.capacity() {
-                let r = unsafe { page.insert(value) };
-                self.header.first_free_page.set(page as *const _); // Set this page as the first with free slots
-                return Either::Left(StrongRef::new(r));
-            }
+        let slot = unsafe { page.insert(value) };
 
-            if let Some(next_free_page) = page.header().next_free_page.get() {
-                page = unsafe { &*next_free_page };
-            } else {
-                break;
-            }
+        if page.is_full() {
+            self.header.pop_free_page();
         }
 
-        Either::Right(value)
+        Either::Left(StrongRef::new(slot.item()))
     }
 
     /// Inserts a new item into the pool
     /// If there is a free slot, creates and returns a strong reference to that slot,
-    /// otherwise a new slot page of size [page_len()] will be added and the item is placed inside it
+    /// otherwise a new slot page of size [RcPool::page_len] will be added and the item is placed inside it.
+    /// Panics if the pool limits doesn't allow adding a page.
     #[must_use]
-    pub fn insert(&self, value: T) -> StrongRef<T, MANUAL_DROP> {
+    pub fn insert(&self, value: T) -> StrongRef<'_, T, MANUAL_DROP> {
+        self.checked_insert(value).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Inserts a new item into the pool like [RcPool::insert] and pins it. Items never move while in the pool,
+    /// since pages are never reallocated and only empty pages are free'd, and pinned items are always dropped in
+    /// place. Moving a pinned item out of the pool with `take_item` or mutably borrowing it without pinning fails
+    /// with [PoolError::Pinned], also through references obtained from weak references, keys or iterators.
+    #[must_use]
+    pub fn insert_pinned(&self, value: T) -> Pin<StrongRef<'_, T, MANUAL_DROP>> {
+        let r = self.insert(value);
+        r.slot().pin();
+        unsafe { Pin::new_unchecked(r) }
+    }
+
+    /// Inserts a new item into the pool like [RcPool::insert], but returns the only reference to it,
+    /// which gives mutable access without runtime checks
+    #[must_use]
+    pub fn insert_unique(&self, value: T) -> UniqueRef<'_, T, MANUAL_DROP> {
+        UniqueRef::new(self.insert(value))
+    }
+
+    /// Inserts a new item into the pool
+    /// If there is a free slot, creates and returns a strong reference to that slot,
+    /// otherwise a new slot page is added if the pool limits allows it and the item is placed inside it.
+    /// If the limits have been reached or the page couldn't be allocated, the item is returned in the error.
+    pub fn checked_insert(&self, value: T) -> Result<StrongRef<'_, T, MANUAL_DROP>, PoolFull<T>> {
         match self.try_insert(value) {
-            Either::Left(r) => r,
+            Either::Left(r) => Ok(r),
 
             Either::Right(v) => {
-                StrongRef::new(unsafe { self.add_page(self.page_len.get()).insert(v) })
+                if let Err(reason) = self.grow() {
+                    let limits = self.limits();
+
+                    return Err(PoolFull {
+                        value: v,
+                        limits,
+                        reason,
+                        limit: limits.reached(self.page_count(), self.capacity()),
+                    });
+                }
+
+                match self.try_insert(v) {
+                    Either::Left(r) => Ok(r),
+                    Either::Right(_) => unreachable!("New page is full!"),
+                }
             }
         }
     }
 
+    /// Returns a weak reference to the item identified by the key, if it's still in the pool, without marking
+    /// its page as having weak references
+    #[must_use]
+    fn find(&self, key: Key) -> Option<WeakRef<'_, T, MANUAL_DROP>> {
+        let slot = self.header.find_page(key.id())?.find(key)?;
+        Some(WeakRef::new_untracked(slot.item()))
+    }
+
+    /// Returns a weak reference to the item identified by the key, if it's still in the pool
+    #[must_use]
+    pub fn weak(&self, key: Key) -> Option<WeakRef<'_, T, MANUAL_DROP>> {
+        let slot = self.header.find_page(key.id())?.find(key)?;
+        Some(WeakRef::new(slot.item()))
+    }
+
+    /// Returns a counted weak reference to the item identified by the key, if it's still in the pool
+    #[must_use]
+    pub fn counted_weak(&self, key: Key) -> Option<CountedWeakRef<'_, T, MANUAL_DROP>> {
+        self.find(key).map(CountedWeakRef::new)
+    }
+
+    /// Returns a strong reference to the item identified by the key, if it's still in the pool and isn't
+    /// mutably borrowed, see [RcPool::try_get]
+    #[must_use]
+    pub fn get(&self, key: Key) -> Option<StrongRef<'_, T, MANUAL_DROP>> {
+        self.try_get(key).ok()
+    }
+
+    /// Returns a strong reference to the item identified by the key,
+    /// or an error if it has been removed or is mutably borrowed
+    pub fn try_get(&self, key: Key) -> Result<StrongRef<'_, T, MANUAL_DROP>, PoolError> {
+        self.find(key).ok_or(PoolError::Removed)?.try_strong()
+    }
+
     fn first_page(&self) -> &Page<T> {
-        unsafe { &*self.header.first_page.get() }
-            .as_deref()
-            .unwrap()
+        unsafe { &*self.header.first_page.get() }.as_ref().unwrap()
+    }
+
+    #[must_use]
+    pub(crate) fn header(&self) -> &PoolHeader<T> {
+        &self.header
+    }
+
+    /// Removes all empty pages from the pool, except one if all pages are empty, so that the pool capacity shrinks.
+    /// Pages that a [WeakRef] has been created into are kept, since those aren't counted. Removed pages that no
+    /// [CountedWeakRef]s point into are free'd right away. The others are free'd by a later call once their
+    /// counted weak references have been dropped, or when the pool is dropped, and until then the references
+    /// are invalid. While an iterator over the pool is alive, only such pages are free'd.
+    pub fn shrink_to_fit(&self) {
+        self.header.free_retired_pages();
+
+        if self.header.iterators.get() > 0 {
+            return;
+        }
+
+        // Iterators are the only references into the page list which are kept outside of the pool's methods,
+        // and strong references keep their pages from being empty, so the pages can be relinked here
+        let first_page = unsafe { &mut *self.header.first_page.get() };
+        let mut pages = Vec::new();
+        let mut page = first_page.take();
+
+        while let Some(mut p) = page {
+            page = p.take_next_page();
+            pages.push(p);
+        }
+
+        let any_live = pages.iter().any(|p| p.len() > 0);
+        self.header.first_free_page.set(null());
+        self.header.pages.borrow_mut().clear();
+        let mut retired = self.header.retired.borrow_mut();
+
+        for (i, mut p) in pages.into_iter().enumerate().rev() {
+            let remove =
+                if any_live { p.len() == 0 } else { i > 0 } && !p.header().weak_refs().get();
+
+            if remove {
+                let header = p.header();
+                header.next_free_page.set(null());
+                let version = header.max_version();
+                self.header
+                    .release_ids(header.first_id(), header.capacity(), version);
+
+                if p.header().weak_count().get() > 0 {
+                    retired.push(p);
+                }
+
+                continue;
+            }
+
+            p.set_next_page(first_page.take());
+            self.header.pages.borrow_mut().push(p.header() as *const _);
+
+            if !p.header().is_full() {
+                self.header.push_free_page(p.header());
+            }
+
+            *first_page = Some(p);
+        }
+
+        let mut pages = self.header.pages.borrow_mut();
+        pages.sort_unstable_by_key(|&p| unsafe { (*p).first_id() });
+    }
+
+    pub(crate) fn pages(&self) -> PageIterator<'_> {
+        PageIterator::new(Some(self.first_page().header()))
+    }
+
+    /// Returns an iterator over strong references to all items, except those that are mutably borrowed
+    #[must_use]
+    pub fn iter(&self) -> RcPoolIterator<'_, T, MANUAL_DROP> {
+        RcPoolIterator::new(
+            Some(self.first_page().header()),
+            Some(&self.header.iterators),
+        )
+    }
+
+    /// Returns an iterator over weak references to all items, which doesn't change any strong counts
+    #[must_use]
+    pub fn iter_weak(&self) -> WeakRefIterator<'_, T, MANUAL_DROP> {
+        WeakRefIterator::new(
+            Some(self.first_page().header()),
+            Some(&self.header.iterators),
+        )
     }
 
+    /// Returns an iterator over shared references to all items, which doesn't change any strong counts.
+    /// It requires a mutable pool reference, since otherwise an item could be mutably borrowed through
+    /// a strong reference while it's referenced.
     #[must_use]
-    pub fn iter(&self) -> RcPoolIterator<T, MANUAL_DROP> {
-        RcPoolIterator {
-            page: Some(self.first_page() as *const _),
-            index: 0,
-            phantom: PhantomData::default(),
+    pub fn values(&mut self) -> ValueIterator<'_, T> {
+        ValueIterator::new(Some(self.first_page().header()))
+    }
+
+    /// Returns an iterator over mutable references to all items, except pinned items
+    #[must_use]
+    pub fn iter_mut(&mut self) -> ValueMutIterator<'_, T> {
+        ValueMutIterator::new(Some(self.first_page().header()))
+    }
+
+    /// Returns an iterator which removes the items that have no strong references and aren't pinned,
+    /// and yields them by value. In automatic drop mode items always have strong references,
+    /// so only items of a manual drop pool are removed. Items that haven't been yielded when the iterator
+    /// is dropped stay in the pool.
+    #[must_use]
+    pub fn drain(&self) -> DrainIterator<'_, T, MANUAL_DROP> {
+        DrainIterator::new(
+            Some(self.first_page().header()),
+            Some(&self.header.iterators),
+        )
+    }
+
+    /// Removes the items for which `f` returns false and that have no strong references other than the one
+    /// passed to `f`, dropping them in place. Returns weak references to the items that should have been removed
+    /// but had to be skipped, because they have strong references or are mutably borrowed (`f` isn't called for
+    /// mutably borrowed items). Weak references to removed items become invalid.
+    pub fn retain(&self, mut f: impl FnMut(&T) -> bool) -> Vec<WeakRef<'_, T, MANUAL_DROP>> {
+        let mut skipped = Vec::new();
+
+        for w in self.iter_weak() {
+            match w.try_strong() {
+                Ok(r) if !f(&r) => {
+                    if r.try_drop_item().is_err() {
+                        skipped.push(w);
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => skipped.push(w),
+            }
         }
+
+        skipped
+    }
+
+    /// Removes all items that have no strong references and aren't mutably borrowed, and returns weak references
+    /// to the skipped items. The versions of the removed items' slots are incremented, so weak references
+    /// to them become invalid.
+    pub fn clear(&self) -> Vec<WeakRef<'_, T, MANUAL_DROP>> {
+        self.retain(|_| false)
     }
 }
 
-pub struct RcPoolIterator<'t, T, const MANUAL_DROP: bool> {
-    page: Option<*const Page<T>>,
-    index: Index,
-    phantom: PhantomData<&'t mut ()>,
+impl<T, const MANUAL_DROP: bool> Pool for RcPool<T, MANUAL_DROP> {
+    type Item = T;
+    type Ref<'t> = StrongRef<'t, T, MANUAL_DROP> where Self: 't;
+
+    fn insert(&self, value: T) -> Self::Ref<'_> {
+        RcPool::insert(self, value)
+    }
 }
 
-impl<'t, T: 't, const MANUAL_DROP: bool> Iterator for RcPoolIterator<'t, T, MANUAL_DROP> {
-    type Item = StrongRef<'t, T, MANUAL_DROP>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        while let Some(p) = self.page {
-            let page = unsafe { &*p };
-
-            if self.index >= page.capacity() {
-                self.page = page
-                    .header()
-                    .next_page
-                    .as_ref()
-                    .map(|p| p.deref() as *const _);
-
-                self.index = 0;
-            } else if let Some(r) = unsafe { page.get(self.index) } {
-                self.index += 1;
-                return Some(StrongRef::new(r));
-            } else {
-                self.index += 1;
-            }
+impl<T, const MANUAL_DROP: bool> Drop for RcPool<T, MANUAL_DROP> {
+    /// Drops all live items in page order, then slot order (the same order as [RcPool::iter]),
+    /// and frees all pages
+    fn drop(&mut self) {
+        let mut page = self.header.first_page.get_mut().take();
+
+        while let Some(mut p) = page {
+            page = p.take_next_page();
         }
 
-        None
+        self.header.retired.get_mut().clear();
     }
 }
@@ -0,0 +1,164 @@
+use crate::{CellTrait, Count, PoolError, StrongRef, WeakRef, MUT_REF_COUNT};
+use core::{
+    cell::{Cell, UnsafeCell},
+    ops::{Deref, DerefMut},
+};
+
+/// Pool item wrapper which allows RefCell-style borrows through any strong or weak reference to the item,
+/// regardless of the strong count. The value can only be accessed through the [CellRef] and [CellRefMut] guards,
+/// which keep a strong reference to the item while they exist.
+pub struct PoolCell<T> {
+    borrow_count: Cell<Count>, // Number of shared borrows, or MUT_REF_COUNT if mutably borrowed
+    value: UnsafeCell<T>,
+}
+
+impl<T> PoolCell<T> {
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self {
+            borrow_count: Cell::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Returns a mutable reference to the value without any borrow checks, since `self` is unique
+    #[must_use]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Returns true if the value is currently mutably borrowed
+    #[must_use]
+    pub fn is_mutably_borrowed(&self) -> bool {
+        self.borrow_count.get() == MUT_REF_COUNT
+    }
+
+    /// Returns the number of active shared borrows
+    #[must_use]
+    pub fn borrow_count(&self) -> usize {
+        match self.borrow_count.get() {
+            MUT_REF_COUNT => 0,
+            count => count as usize,
+        }
+    }
+}
+
+impl<T> From<T> for PoolCell<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Default> Default for PoolCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// A shared borrow of a [PoolCell] item, similar to [core::cell::Ref]
+pub struct CellRef<'t, T, const MANUAL_DROP: bool> {
+    r: StrongRef<'t, PoolCell<T>, MANUAL_DROP>,
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Deref for CellRef<'t, T, MANUAL_DROP> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.r.value.get() }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Drop for CellRef<'t, T, MANUAL_DROP> {
+    fn drop(&mut self) {
+        self.r.borrow_count.sub(1);
+    }
+}
+
+/// A mutable borrow of a [PoolCell] item, similar to [core::cell::RefMut]
+pub struct CellRefMut<'t, T, const MANUAL_DROP: bool> {
+    r: StrongRef<'t, PoolCell<T>, MANUAL_DROP>,
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Deref for CellRefMut<'t, T, MANUAL_DROP> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.r.value.get() }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> DerefMut for CellRefMut<'t, T, MANUAL_DROP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.r.value.get() }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Drop for CellRefMut<'t, T, MANUAL_DROP> {
+    fn drop(&mut self) {
+        self.r.borrow_count.set(0);
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> StrongRef<'t, PoolCell<T>, MANUAL_DROP> {
+    /// Borrows the value, panics if it's mutably borrowed
+    #[must_use]
+    pub fn borrow(&self) -> CellRef<'t, T, MANUAL_DROP> {
+        self.try_borrow().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Borrows the value, or returns an error if it's mutably borrowed
+    pub fn try_borrow(&self) -> Result<CellRef<'t, T, MANUAL_DROP>, PoolError> {
+        self.clone().into_borrow()
+    }
+
+    /// Mutably borrows the value, panics if it's already borrowed
+    #[must_use]
+    pub fn borrow_mut(&self) -> CellRefMut<'t, T, MANUAL_DROP> {
+        self.try_borrow_mut().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Mutably borrows the value, or returns an error if it's already borrowed
+    pub fn try_borrow_mut(&self) -> Result<CellRefMut<'t, T, MANUAL_DROP>, PoolError> {
+        self.clone().into_borrow_mut()
+    }
+
+    /// Same as [StrongRef::try_borrow], but reuses this strong reference for the guard
+    pub fn into_borrow(self) -> Result<CellRef<'t, T, MANUAL_DROP>, PoolError> {
+        match self.borrow_count.get() {
+            MUT_REF_COUNT => Err(PoolError::MutablyBorrowed),
+
+            _ => {
+                self.borrow_count.add(1);
+                Ok(CellRef { r: self })
+            }
+        }
+    }
+
+    /// Same as [StrongRef::try_borrow_mut], but reuses this strong reference for the guard
+    pub fn into_borrow_mut(self) -> Result<CellRefMut<'t, T, MANUAL_DROP>, PoolError> {
+        match self.borrow_count.get() {
+            0 => {
+                self.borrow_count.set(MUT_REF_COUNT);
+                Ok(CellRefMut { r: self })
+            }
+
+            MUT_REF_COUNT => Err(PoolError::MutablyBorrowed),
+            _ => Err(PoolError::Borrowed),
+        }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> WeakRef<'t, PoolCell<T>, MANUAL_DROP> {
+    /// Borrows the value, or returns an error if it has been removed or is mutably borrowed
+    pub fn try_borrow(&self) -> Result<CellRef<'t, T, MANUAL_DROP>, PoolError> {
+        self.try_strong()?.into_borrow()
+    }
+
+    /// Mutably borrows the value, or returns an error if it has been removed or is already borrowed
+    pub fn try_borrow_mut(&self) -> Result<CellRefMut<'t, T, MANUAL_DROP>, PoolError> {
+        self.try_strong()?.into_borrow_mut()
+    }
+}
@@ -1,52 +1,114 @@
-use super::{Count, Index, Version};
-use std::{
+use super::{page::PageHeader, Count, Version};
+use crate::MUT_REF_COUNT;
+use core::{
     cell::{Cell, UnsafeCell},
-    mem::MaybeUninit,
+    mem::{size_of, MaybeUninit},
     num::NonZeroUsize,
+    ptr::{self, NonNull},
 };
 
-pub struct Slot<T> {
-    pub(crate) item: UnsafeCell<MaybeUninit<T>>,
+/// The part of a slot which doesn't depend on the item type. It's stored right before the item, so it can be
+/// found from a pointer to the item, also for references to unsized items (see [crate::StrongRef::unsize]).
+pub(crate) struct SlotHeader {
     pub(crate) version: Cell<Version>,
-    pub(crate) count: Cell<Count>,
-    pub(crate) index: Cell<Index>,
+    pub(crate) count: Cell<Count>, // Number of strong references, or the offset of the next free slot if this slot is free
+    offset: Cell<u32>, // Distance in bytes from the page header to this slot header, the highest bit is the pinned flag
 }
 
-impl<T> Slot<T> {
+/// Set in [SlotHeader::offset] if the item is pinned, see [crate::RcPool::insert_pinned]
+const PINNED: u32 = 1 << 31;
+
+impl SlotHeader {
+    /// Creates the header of a free slot with the given offset, next free slot and version, which must be odd
     #[must_use]
-    pub(crate) unsafe fn get(&self) -> &T {
-        debug_assert!(self.count.get() > 0);
-        (*self.item.get()).assume_init_ref()
+    pub(crate) fn new(offset: u32, next_free: u32, version: Version) -> Self {
+        debug_assert!(version.get() & 1 == 1);
+
+        Self {
+            version: Cell::new(version),
+            count: Cell::new(next_free),
+            offset: Cell::new(offset),
+        }
     }
 
+    /// Distance in bytes from the page header to this slot header
     #[must_use]
-    #[allow(clippy::mut_from_ref)]
-    pub(crate) unsafe fn get_mut(&self) -> &mut T {
-        (*self.item.get()).assume_init_mut()
+    pub(crate) fn offset(&self) -> u32 {
+        self.offset.get() & !PINNED
     }
 
-    pub(crate) unsafe fn set_value(&self, value: T) {
-        debug_assert!(self.is_free());
-        debug_assert!(self.count.get() == 0);
-        (*self.item.get()).write(value);
-        self.incr_version();
+    /// Returns true if the item may not be moved out of the slot until it's dropped
+    #[must_use]
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.offset.get() & PINNED != 0
+    }
+
+    pub(crate) fn pin(&self) {
+        debug_assert!(!self.is_free());
+        self.offset.set(self.offset.get() | PINNED);
+    }
+
+    /// Returns the header of the slot containing the item
+    #[must_use]
+    pub(crate) unsafe fn of<'t, T: ?Sized>(item: *const T) -> &'t SlotHeader {
+        &*(item as *const u8)
+            .sub(size_of::<SlotHeader>())
+            .cast::<SlotHeader>()
+    }
+
+    /// Returns a pointer to the item of this slot, which must be of type `T`
+    #[must_use]
+    pub(crate) fn item<T>(&self) -> NonNull<T> {
+        unsafe {
+            NonNull::from(self)
+                .cast::<u8>()
+                .add(size_of::<SlotHeader>())
+                .cast()
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn page(&self) -> &PageHeader {
+        unsafe { PageHeader::of(self) }
     }
 
     pub(crate) fn is_free(&self) -> bool {
         self.version.get().get() & 1 == 1
     }
 
-    fn incr_version(&self) {
+    pub(crate) fn incr_version(&self) {
         self.version
             .set(unsafe { NonZeroUsize::new_unchecked(self.version.get().get() + 1) });
     }
 
-    unsafe fn head(&self) -> &Slot<T> {
-        unsafe { &*(self as *const Slot<T>).offset(-(self.index.get() as isize)) }
+    /// Marks the item as mutably borrowed, which must be its only strong reference
+    pub(crate) fn borrow_mut(&self) {
+        debug_assert!(self.count.get() <= 1);
+        self.count.set(MUT_REF_COUNT);
+        let borrowed = self.page().borrowed();
+        borrowed.set(borrowed.get() + 1);
+    }
+
+    /// Ends a mutable borrow of the item, leaving it with `count` strong references
+    pub(crate) fn end_borrow_mut(&self, count: Count) {
+        debug_assert!(self.count.get() == MUT_REF_COUNT);
+        self.count.set(count);
+        let borrowed = self.page().borrowed();
+        borrowed.set(borrowed.get() - 1);
     }
 
-    pub(crate) fn take_item(&self) -> T {
+    /// Moves the value into this slot, which must be free
+    pub(crate) unsafe fn set_value<T>(&self, value: T) {
+        debug_assert!(self.is_free());
+        debug_assert!(self.count.get() == 0);
+        self.item::<T>().write(value);
+        self.incr_version();
+    }
+
+    /// Moves the item, which must be of type `T`, out of this slot and releases the slot
+    pub(crate) unsafe fn take_item<T>(&self) -> T {
         debug_assert!(!self.is_free());
+        debug_assert!(!self.is_pinned(), "Can't take pinned item!");
 
         debug_assert!(
             self.count.get() == 0,
@@ -54,21 +116,62 @@ impl<T> Slot<T> {
         );
 
         self.incr_version();
-        let index = self.index.get();
-        let head = unsafe { self.head() };
-        self.index.set(head.index.get());
-        head.index.set(index);
-        unsafe { (*self.item.get()).assume_init_read() }
+        let value = self.item::<T>().read();
+        self.page().release(self);
+        value
+    }
+
+    /// Drops the item with the destructor of the page's item type and releases the slot. The slot is also
+    /// released if the destructor panics, so that the item isn't dropped again when the pool is dropped.
+    pub(crate) fn drop_item(&self) {
+        debug_assert!(!self.is_free());
+
+        debug_assert!(
+            self.count.get() == 0,
+            "Can't drop item with strong references!"
+        );
+
+        let page = self.page();
+        self.incr_version();
+        self.offset.set(self.offset());
+        let _release = ReleaseGuard(self);
+        unsafe { page.drop_item(self) };
     }
 }
 
-impl<T> Default for Slot<T> {
-    fn default() -> Self {
-        Self {
-            item: UnsafeCell::new(MaybeUninit::uninit()),
-            version: unsafe { NonZeroUsize::new_unchecked(1).into() },
-            count: 0.into(),
-            index: 0.into(),
-        }
+/// Releases a slot when it's dropped, after dropping its item or while unwinding from the item's destructor
+struct ReleaseGuard<'t>(&'t SlotHeader);
+
+impl<'t> Drop for ReleaseGuard<'t> {
+    fn drop(&mut self) {
+        self.0.page().release(self.0);
     }
 }
+
+/// Space for a [SlotHeader], aligned so that the item can follow right after the header
+#[repr(C)]
+struct HeaderSpace<T> {
+    align: [T; 0],
+    header: MaybeUninit<SlotHeader>,
+}
+
+/// Memory layout of a slot in an [crate::RcPool] page. The slot header is stored in the last bytes of the header space,
+/// right before the item.
+#[repr(C)]
+pub(crate) struct Slot<T> {
+    header: UnsafeCell<MaybeUninit<HeaderSpace<T>>>,
+    item: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    /// Distance in bytes from the start of a slot to its header
+    pub(crate) const HEADER_OFFSET: usize = size_of::<HeaderSpace<T>>() - size_of::<SlotHeader>();
+}
+
+/// Drops the item of a slot in place
+pub(crate) type DropItemFn = unsafe fn(NonNull<SlotHeader>);
+
+/// Drops the item of a slot, which must be occupied by an item of type `T`
+pub(crate) unsafe fn drop_slot_item<T>(slot: NonNull<SlotHeader>) {
+    ptr::drop_in_place(slot.as_ref().item::<T>().as_ptr())
+}
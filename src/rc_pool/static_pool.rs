@@ -0,0 +1,127 @@
+use super::{
+    page::{PageHeader, PageLayout, MAX_PAGE_SIZE},
+    slot::Slot,
+};
+use crate::{Either, Index, Key, PoolError, RcPoolIterator, StrongRef, Version, WeakRef};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::{align_of, size_of, size_of_val, MaybeUninit},
+    ptr::{null, NonNull},
+};
+
+/// Storage for one slot of a [StaticRcPool], see [StaticRcPool::new]
+#[repr(transparent)]
+pub struct StaticSlot<T>(Slot<T>);
+
+/// A pool with a fixed capacity which stores its items in a caller provided buffer, so it never allocates
+/// any memory. The buffer can for example be a static or an array on the stack, created with
+/// `[const { MaybeUninit::uninit() }; N]`.
+pub struct StaticRcPool<'b, T, const MANUAL_DROP: bool> {
+    page: NonNull<PageHeader>,
+    phantom: PhantomData<&'b mut [StaticSlot<T>]>,
+}
+
+impl<'b, T, const MANUAL_DROP: bool> StaticRcPool<'b, T, MANUAL_DROP> {
+    /// Creates a pool which stores its items in the buffer. The first buffer elements are used for the page header
+    /// and the last ones for the occupancy bitmap, which takes one bit per slot, so the pool capacity is a few
+    /// elements less than the buffer length, see [StaticRcPool::capacity]. Panics if the buffer has no room
+    /// for the page header.
+    #[must_use]
+    pub fn new(buffer: &'b mut [MaybeUninit<StaticSlot<T>>]) -> Self {
+        let slot_size = size_of::<StaticSlot<T>>();
+        let header_slots = size_of::<PageHeader>().div_ceil(slot_size);
+        assert!(buffer.len() >= header_slots, "No room for the page header!");
+        assert!(size_of_val(buffer) <= MAX_PAGE_SIZE, "Buffer too large!");
+        debug_assert!(align_of::<StaticSlot<T>>() >= align_of::<PageHeader>());
+
+        let slots = buffer.len() - header_slots;
+        let bitmap_size = PageHeader::occupancy_layout(slots as Index).unwrap().size();
+        let cap = slots - bitmap_size.div_ceil(slot_size);
+
+        let layout = PageLayout {
+            layout: Layout::for_value(&*buffer),
+            first_slot: header_slots * slot_size,
+            occupancy: (header_slots + cap) * slot_size,
+        };
+
+        let page = NonNull::from(buffer).cast::<u8>();
+        unsafe { PageHeader::init::<T>(page, &layout, null(), cap as Index, 0, Version::MIN) };
+
+        Self {
+            page: page.cast(),
+            phantom: PhantomData,
+        }
+    }
+
+    fn page(&self) -> &PageHeader {
+        unsafe { self.page.as_ref() }
+    }
+
+    /// Inserts a new item into the pool
+    /// If there is a free slot, creates and returns a strong reference to that slot,
+    /// otherwise returns the item
+    #[must_use]
+    pub fn try_insert(&self, value: T) -> Either<StrongRef<'_, T, MANUAL_DROP>, T> {
+        let page = self.page();
+
+        if page.is_full() {
+            Either::Right(value)
+        } else {
+            Either::Left(StrongRef::new(unsafe { page.insert(value) }.item()))
+        }
+    }
+
+    /// Returns the number of live items in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.page().len() as usize
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.page().capacity() as usize
+    }
+
+    #[must_use]
+    pub fn free_slots(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    /// Returns a weak reference to the item identified by the key, if it's still in the pool
+    #[must_use]
+    pub fn weak(&self, key: Key) -> Option<WeakRef<'_, T, MANUAL_DROP>> {
+        Some(WeakRef::new(self.page().find(key)?.item()))
+    }
+
+    /// Returns a strong reference to the item identified by the key, if it's still in the pool and isn't
+    /// mutably borrowed, see [StaticRcPool::try_get]
+    #[must_use]
+    pub fn get(&self, key: Key) -> Option<StrongRef<'_, T, MANUAL_DROP>> {
+        self.try_get(key).ok()
+    }
+
+    /// Returns a strong reference to the item identified by the key,
+    /// or an error if it has been removed or is mutably borrowed
+    pub fn try_get(&self, key: Key) -> Result<StrongRef<'_, T, MANUAL_DROP>, PoolError> {
+        self.weak(key).ok_or(PoolError::Removed)?.try_strong()
+    }
+
+    /// Returns an iterator over strong references to all items, except those that are mutably borrowed
+    #[must_use]
+    pub fn iter(&self) -> RcPoolIterator<'_, T, MANUAL_DROP> {
+        RcPoolIterator::new(Some(self.page()), None)
+    }
+}
+
+impl<'b, T, const MANUAL_DROP: bool> Drop for StaticRcPool<'b, T, MANUAL_DROP> {
+    /// Drops all live items in slot order
+    fn drop(&mut self) {
+        unsafe { self.page().drop_items() }
+    }
+}
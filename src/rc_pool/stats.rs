@@ -0,0 +1,91 @@
+use super::{
+    iter::IterGuard,
+    page::{Page, PageIterator},
+};
+use crate::{Index, PoolHeader, RcPool};
+use core::mem::size_of;
+
+/// Occupancy of a single pool page
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct PageStats {
+    pub len: Index,
+    pub capacity: Index,
+}
+
+impl PageStats {
+    #[must_use]
+    pub fn free_slots(&self) -> Index {
+        self.capacity - self.len
+    }
+}
+
+impl<T, const MANUAL_DROP: bool> RcPool<T, MANUAL_DROP> {
+    /// Returns the number of live items in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pages().map(|p| p.len() as usize).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pages().all(|p| p.len() == 0)
+    }
+
+    /// Returns the total number of item slots in all pages
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.pages().map(|p| p.capacity() as usize).sum()
+    }
+
+    /// Returns the number of slots that can be filled without adding a page
+    #[must_use]
+    pub fn free_slots(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.pages().count()
+    }
+
+    /// Returns the number of heap bytes allocated by the pool, not counting allocator bookkeeping
+    /// or any heap memory owned by the items themselves
+    #[must_use]
+    pub fn allocated_bytes(&self) -> usize {
+        size_of::<PoolHeader<T>>()
+            + self.pages().map(|p| p.allocated_bytes()).sum::<usize>()
+            + self.header().retired_bytes()
+    }
+
+    /// Returns the number of bytes each slot uses in addition to the item itself.
+    /// Each page also has a header, padded to the slot alignment, and one bit per slot for its occupancy bitmap.
+    #[must_use]
+    pub const fn slot_overhead() -> usize {
+        Page::<T>::SLOT_SIZE - size_of::<T>()
+    }
+
+    /// Returns the occupancy of each page, in page order
+    #[must_use]
+    pub fn page_stats(&self) -> PageStatsIterator<'_> {
+        PageStatsIterator {
+            pages: self.pages(),
+            _guard: IterGuard::new(Some(self.header().iterators())),
+        }
+    }
+}
+
+pub struct PageStatsIterator<'t> {
+    pages: PageIterator<'t>,
+    _guard: IterGuard<'t>,
+}
+
+impl<'t> Iterator for PageStatsIterator<'t> {
+    type Item = PageStats;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pages.next().map(|p| PageStats {
+            len: p.len(),
+            capacity: p.capacity(),
+        })
+    }
+}
@@ -1,162 +1,329 @@
-use super::slot::Slot;
-use crate::{CellTrait, Either, StrongRefTrait, WeakRef, WeakRefTrait};
-use std::ops::{Deref, DerefMut};
+use super::slot::SlotHeader;
+use crate::{CellTrait, CountedWeakRef, Key, PoolError, StrongRefTrait, WeakRef};
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    ptr::NonNull,
+};
 
 pub(crate) const MUT_REF_COUNT: u32 = u32::MAX;
 
-pub struct RefMut<'t, 'u, T, const MANUAL_DROP: bool> {
+pub struct RefMut<'t, 'u, T: ?Sized, const MANUAL_DROP: bool> {
     r: &'t mut StrongRef<'u, T, MANUAL_DROP>,
 }
 
-impl<'t, 'u, T, const MANUAL_DROP: bool> Deref for RefMut<'t, 'u, T, MANUAL_DROP> {
+impl<'t, 'u, T: ?Sized, const MANUAL_DROP: bool> Deref for RefMut<'t, 'u, T, MANUAL_DROP> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        unsafe { self.r.slot.get() }
+        unsafe { self.r.item.as_ref() }
     }
 }
 
-impl<'t, 'u, T, const MANUAL_DROP: bool> DerefMut for RefMut<'t, 'u, T, MANUAL_DROP> {
+impl<'t, 'u, T: ?Sized, const MANUAL_DROP: bool> DerefMut for RefMut<'t, 'u, T, MANUAL_DROP> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { self.r.slot.get_mut() }
+        unsafe { self.r.item.as_mut() }
     }
 }
 
-impl<'t, 'u, T, const MANUAL_DROP: bool> Drop for RefMut<'t, 'u, T, MANUAL_DROP> {
+impl<'t, 'u, T: ?Sized, const MANUAL_DROP: bool> Drop for RefMut<'t, 'u, T, MANUAL_DROP> {
     fn drop(&mut self) {
-        self.r.slot.count.set(1); // We know there's only one strong reference at this point
+        self.r.slot().end_borrow_mut(1); // We know there's only one strong reference at this point
     }
 }
 
-pub struct StrongRef<'t, T, const MANUAL_DROP: bool> {
-    slot: &'t Slot<T>,
+/// A strong reference to an item in a pool. The item type may be unsized, see [crate::unsize].
+///
+/// References are invariant in `T`, since the item can be mutated through them, so a reference can't be
+/// converted to a reference to an item type with shorter lifetimes:
+/// ```compile_fail
+/// use rc_pool::StrongRef;
+///
+/// fn shorten<'t, 'a>(r: StrongRef<'t, &'static str, true>) -> StrongRef<'t, &'a str, true> {
+///     r
+/// }
+/// ```
+///
+/// Neither through [crate::unsize], which only allows unsizing coercions:
+/// ```compile_fail
+/// use rc_pool::{unsize, StrongRef};
+///
+/// fn shorten<'t, 'a>(r: StrongRef<'t, &'static str, true>) -> StrongRef<'t, &'a str, true> {
+///     unsize!(r => &'a str)
+/// }
+/// ```
+pub struct StrongRef<'t, T: ?Sized, const MANUAL_DROP: bool> {
+    item: NonNull<T>,
+    phantom: PhantomData<&'t UnsafeCell<T>>, // Invariant in T
 }
 
-impl<'t, T, const MANUAL_DROP: bool> StrongRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> StrongRef<'t, T, MANUAL_DROP> {
+    /// Creates a new strong reference to an item in an occupied slot
     #[must_use]
-    pub(crate) fn new(slot: &'t Slot<T>) -> Self {
-        slot.count.add(1);
-        Self { slot }
+    pub(crate) fn new(item: NonNull<T>) -> Self {
+        let r = Self {
+            item,
+            phantom: PhantomData,
+        };
+
+        r.slot().count.add(1);
+        r
+    }
+
+    #[must_use]
+    pub(crate) fn slot(&self) -> &'t SlotHeader {
+        unsafe { SlotHeader::of(self.item.as_ptr()) }
     }
 
     #[must_use]
     pub fn get_mut<'u>(&'u mut self) -> RefMut<'u, 't, T, MANUAL_DROP> {
-        self.try_get_mut().expect("More than one strong reference!")
+        self.try_get_mut().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns a mutable reference to the item, or an error if this isn't the only strong reference
+    /// or if the item is pinned
+    pub fn try_get_mut<'u>(&'u mut self) -> Result<RefMut<'u, 't, T, MANUAL_DROP>, PoolError> {
+        if self.slot().is_pinned() {
+            return Err(PoolError::Pinned);
+        }
+
+        self.borrow_unique()
+    }
+
+    /// Returns a pinned mutable reference to a pinned item, or an error if this isn't the only strong reference
+    pub fn try_get_pin_mut<'u>(
+        this: &'u mut Pin<Self>,
+    ) -> Result<Pin<RefMut<'u, 't, T, MANUAL_DROP>>, PoolError> {
+        // The strong reference itself is never moved and the item stays pinned in the returned reference
+        let r = unsafe { &mut *(this as *mut Pin<Self>).cast::<Self>() };
+        Ok(unsafe { Pin::new_unchecked(r.borrow_unique()?) })
+    }
+
+    #[must_use]
+    pub fn get_pin_mut<'u>(this: &'u mut Pin<Self>) -> Pin<RefMut<'u, 't, T, MANUAL_DROP>> {
+        Self::try_get_pin_mut(this).unwrap_or_else(|e| panic!("{e}"))
     }
 
+    /// Returns a weak reference to a pinned item. Upgrading it returns an unpinned strong reference, through
+    /// which the item can't be moved or mutably borrowed.
     #[must_use]
-    pub fn try_get_mut<'u>(&'u mut self) -> Option<RefMut<'u, 't, T, MANUAL_DROP>> {
+    pub fn pin_weak(this: &Pin<Self>) -> WeakRef<'t, T, MANUAL_DROP> {
+        WeakRef::new(Self::pin_item(this))
+    }
+
+    /// Returns a compact key identifying a pinned item, which can be resolved through the pool
+    #[must_use]
+    pub fn pin_key(this: &Pin<Self>) -> Key {
+        let slot = unsafe { SlotHeader::of(Self::pin_item(this).as_ptr()) };
+        Key::new(slot, slot.version.get())
+    }
+
+    #[must_use]
+    fn pin_item(this: &Pin<Self>) -> NonNull<T> {
+        NonNull::from(&**this)
+    }
+
+    fn borrow_unique<'u>(&'u mut self) -> Result<RefMut<'u, 't, T, MANUAL_DROP>, PoolError> {
         if self.is_unique() {
-            self.slot.count.set(MUT_REF_COUNT);
-            Some(RefMut { r: self })
+            self.slot().borrow_mut();
+            Ok(RefMut { r: self })
         } else {
-            None
+            Err(PoolError::NotUnique {
+                strong_count: self.strong_count(),
+            })
         }
     }
 
+    pub fn drop_item(self) {
+        self.try_drop_item().unwrap_or_else(|(e, _)| panic!("{e}"))
+    }
+
+    /// Returns a compact key identifying the item, which can be resolved through the pool
     #[must_use]
-    pub fn take_item(self) -> T {
-        match self.try_take_item() {
-            Either::Left(v) => v,
-            Either::Right(_) => panic!("Can't take item with strong references!"),
-        }
+    pub fn key(&self) -> Key {
+        let slot = self.slot();
+        Key::new(slot, slot.version.get())
     }
 
+    /// Returns a counted weak reference to the item, which doesn't keep its page from being removed by
+    /// [crate::RcPool::shrink_to_fit]
     #[must_use]
-    pub fn try_take_item(self) -> Either<T, Self> {
+    pub fn counted_weak(&self) -> CountedWeakRef<'t, T, MANUAL_DROP> {
+        CountedWeakRef::new(WeakRef::new_untracked(self.item))
+    }
+
+    /// Drops the item with the destructor of its concrete type, if this is the only strong reference,
+    /// otherwise returns the error together with `self`. Pinned items can be dropped as well, since they are
+    /// dropped in place.
+    pub fn try_drop_item(self) -> Result<(), (PoolError, Self)> {
+        if let Err(e) = self.check_unique() {
+            return Err((e, self));
+        }
+
+        let slot = self.slot();
+        slot.count.set(0);
+        mem::forget(self);
+        slot.drop_item();
+        Ok(())
+    }
+
+    fn check_unique(&self) -> Result<(), PoolError> {
         if self.is_unique() {
-            let slot = self.slot;
-            drop(self);
-            Either::Left(slot.take_item())
+            Ok(())
         } else {
-            Either::Right(self)
+            Err(PoolError::NotUnique {
+                strong_count: self.strong_count(),
+            })
         }
     }
 
-    pub fn drop_item(self) {
-        assert!(
-            self.try_drop_item().is_none(),
-            "Can't take item with strong references!"
-        );
+    /// Converts the reference to a reference to an unsized type, for which `f` must return `item`
+    /// with added metadata. The pointers are to `UnsafeCell`s, which are invariant, so that a coercion
+    /// in `f` can't shorten any lifetimes in `T`. Use the safe [crate::unsize] macro instead.
+    ///
+    /// # Safety
+    /// The returned pointer must point to the same item and only differ from `item` by its metadata,
+    /// which is guaranteed if it's created by an unsizing coercion.
+    #[doc(hidden)]
+    #[must_use]
+    pub unsafe fn unsize_unchecked<U: ?Sized>(
+        self,
+        f: impl FnOnce(*const UnsafeCell<T>) -> *const UnsafeCell<U>,
+    ) -> StrongRef<'t, U, MANUAL_DROP> {
+        let item = f(self.item.as_ptr() as *const UnsafeCell<T>) as *mut U;
+        debug_assert!(item as *const u8 == self.item.as_ptr() as *const u8);
+        mem::forget(self);
+
+        StrongRef {
+            item: NonNull::new_unchecked(item),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Converts the reference to a reference to the same item as type `U`, which must be the item's type
+    #[must_use]
+    pub(crate) unsafe fn cast<U>(self) -> StrongRef<'t, U, MANUAL_DROP> {
+        let item = self.item.cast();
+        mem::forget(self);
+
+        StrongRef {
+            item,
+            phantom: PhantomData,
+        }
     }
+}
 
+impl<'t, T, const MANUAL_DROP: bool> StrongRef<'t, T, MANUAL_DROP> {
     #[must_use]
-    pub fn try_drop_item(self) -> Option<Self> {
-        match self.try_take_item() {
-            Either::Left(_) => None,
-            Either::Right(s) => Some(s),
+    pub fn take_item(self) -> T {
+        self.try_take_item().unwrap_or_else(|(e, _)| panic!("{e}"))
+    }
+
+    /// Removes the item from the pool and returns it, if this is the only strong reference and the item isn't pinned,
+    /// otherwise returns the error together with `self`
+    pub fn try_take_item(self) -> Result<T, (PoolError, Self)> {
+        if self.slot().is_pinned() {
+            return Err((PoolError::Pinned, self));
         }
+
+        if let Err(e) = self.check_unique() {
+            return Err((e, self));
+        }
+
+        let slot = self.slot();
+        slot.count.set(0);
+        mem::forget(self);
+        Ok(unsafe { slot.take_item() })
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> PartialEq for StrongRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> PartialEq for StrongRef<'t, T, MANUAL_DROP> {
     fn eq(&self, other: &Self) -> bool {
-        std::ptr::eq(self.slot as *const Slot<T>, other.slot as *const Slot<T>)
+        self.item.as_ptr() as *const u8 == other.item.as_ptr() as *const u8
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> Eq for StrongRef<'t, T, MANUAL_DROP> {}
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Eq for StrongRef<'t, T, MANUAL_DROP> {}
 
-impl<'t, T, const MANUAL_DROP: bool> Clone for StrongRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Clone for StrongRef<'t, T, MANUAL_DROP> {
     fn clone(&self) -> Self {
-        Self::new(self.slot)
+        Self::new(self.item)
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> std::hash::Hash for StrongRef<'t, T, MANUAL_DROP> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        (self.slot as *const Slot<T>).hash(state);
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> core::hash::Hash for StrongRef<'t, T, MANUAL_DROP> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.item.as_ptr() as *const u8).hash(state);
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> std::fmt::Debug for StrongRef<'t, T, MANUAL_DROP> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> core::fmt::Debug for StrongRef<'t, T, MANUAL_DROP> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("StrongRef")
-            .field("slot", &(self.slot as *const Slot<T>))
+            .field("item", &(self.item.as_ptr() as *const u8))
             .finish()
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> Deref for StrongRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Deref for StrongRef<'t, T, MANUAL_DROP> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        unsafe { self.slot.get() }
+        unsafe { self.item.as_ref() }
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> Drop for StrongRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Drop for StrongRef<'t, T, MANUAL_DROP> {
     fn drop(&mut self) {
-        self.slot.count.sub(1);
+        let slot = self.slot();
+        slot.count.sub(1);
 
-        if !MANUAL_DROP && self.slot.count.get() == 0 {
-            self.slot.take_item();
+        if !MANUAL_DROP && slot.count.get() == 0 {
+            slot.drop_item();
         }
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> TryFrom<WeakRef<'t, T, MANUAL_DROP>>
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> TryFrom<WeakRef<'t, T, MANUAL_DROP>>
     for StrongRef<'t, T, MANUAL_DROP>
 {
-    type Error = String;
+    type Error = PoolError;
 
     fn try_from(value: WeakRef<'t, T, MANUAL_DROP>) -> Result<Self, Self::Error> {
-        value.strong().ok_or_else(|| "Element removed!".into())
+        value.try_strong()
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> StrongRefTrait for StrongRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> StrongRefTrait for StrongRef<'t, T, MANUAL_DROP> {
     type Weak = WeakRef<'t, T, MANUAL_DROP>;
 
     type RefMut<'u> = RefMut<'u, 't, T, MANUAL_DROP> where Self: 'u;
 
     fn weak(&self) -> Self::Weak {
-        WeakRef::new(self.slot)
+        WeakRef::new(self.item)
     }
 
     fn strong_count(&self) -> usize {
-        self.slot.count.get() as usize
+        self.slot().count.get() as usize
     }
 
     fn get_mut(&mut self) -> Option<Self::RefMut<'_>> {
-        self.try_get_mut()
+        self.try_get_mut().ok()
     }
 }
+
+/// Converts a [StrongRef] or [WeakRef] to a reference to an unsized type, usually a trait object, by an unsizing
+/// coercion of the item pointer: `unsize!(player => dyn Component)`. Counts and versions are still those of the
+/// item's slot and dropping the item through the converted reference runs the destructor of its concrete type.
+/// Only unsizing coercions are accepted, a reference can't be converted to an item type with shorter lifetimes.
+#[macro_export]
+macro_rules! unsize {
+    ($r:expr => $t:ty) => {
+        match $r {
+            r => unsafe {
+                r.unsize_unchecked(|item| -> *const ::core::cell::UnsafeCell<$t> { item })
+            },
+        }
+    };
+}
@@ -0,0 +1,133 @@
+use super::slot::SlotHeader;
+use crate::{Key, StrongRef, StrongRefTrait, WeakRef};
+use core::{
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+/// The only reference to a pool item, similar to a Box. It dereferences mutably without any runtime checks
+/// and can be converted to a [StrongRef] with [UniqueRef::share]. While it exists, weak references to the item
+/// can't be upgraded, as if the item was mutably borrowed.
+///
+/// The functions are associated functions so that they don't shadow any method of `T`.
+pub struct UniqueRef<'t, T: ?Sized, const MANUAL_DROP: bool> {
+    item: NonNull<T>,
+    phantom: PhantomData<&'t mut T>,
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> UniqueRef<'t, T, MANUAL_DROP> {
+    /// Takes over the item of a strong reference, which must be the only strong reference
+    #[must_use]
+    pub(crate) fn new(r: StrongRef<'t, T, MANUAL_DROP>) -> Self {
+        debug_assert!(r.is_unique());
+        let item = NonNull::from(&*r);
+        r.slot().borrow_mut();
+        mem::forget(r);
+
+        Self {
+            item,
+            phantom: PhantomData,
+        }
+    }
+
+    #[must_use]
+    fn slot(this: &Self) -> &'t SlotHeader {
+        unsafe { SlotHeader::of(this.item.as_ptr()) }
+    }
+
+    /// Converts the reference to a strong reference, which can be cloned
+    #[must_use]
+    pub fn share(this: Self) -> StrongRef<'t, T, MANUAL_DROP> {
+        Self::slot(&this).end_borrow_mut(0);
+        let r = StrongRef::new(this.item);
+        mem::forget(this);
+        r
+    }
+
+    /// Returns a weak reference to the item, which can't be upgraded until this reference has been shared or dropped
+    #[must_use]
+    pub fn weak(this: &Self) -> WeakRef<'t, T, MANUAL_DROP> {
+        WeakRef::new(this.item)
+    }
+
+    /// Returns a compact key identifying the item, which can be resolved through the pool
+    #[must_use]
+    pub fn key(this: &Self) -> Key {
+        let slot = Self::slot(this);
+        Key::new(slot, slot.version.get())
+    }
+
+    /// Drops the item with the destructor of its concrete type, also in manual drop mode
+    pub fn drop_item(this: Self) {
+        let slot = Self::slot(&this);
+        slot.end_borrow_mut(0);
+        mem::forget(this);
+        slot.drop_item();
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> UniqueRef<'t, T, MANUAL_DROP> {
+    /// Removes the item from the pool and returns it
+    #[must_use]
+    pub fn take_item(this: Self) -> T {
+        let slot = Self::slot(&this);
+        slot.end_borrow_mut(0);
+        mem::forget(this);
+        unsafe { slot.take_item() }
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> StrongRef<'t, T, MANUAL_DROP> {
+    /// Converts the reference to a [UniqueRef] if it's the only strong reference and the item isn't pinned,
+    /// otherwise returns `self`
+    pub fn into_unique(self) -> Result<UniqueRef<'t, T, MANUAL_DROP>, Self> {
+        if self.is_unique() && !self.slot().is_pinned() {
+            Ok(UniqueRef::new(self))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> From<UniqueRef<'t, T, MANUAL_DROP>>
+    for StrongRef<'t, T, MANUAL_DROP>
+{
+    fn from(r: UniqueRef<'t, T, MANUAL_DROP>) -> Self {
+        UniqueRef::share(r)
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Deref for UniqueRef<'t, T, MANUAL_DROP> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.item.as_ref() }
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> DerefMut for UniqueRef<'t, T, MANUAL_DROP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.item.as_mut() }
+    }
+}
+
+impl<'t, T: ?Sized + core::fmt::Debug, const MANUAL_DROP: bool> core::fmt::Debug
+    for UniqueRef<'t, T, MANUAL_DROP>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("UniqueRef").field(&&**self).finish()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Drop for UniqueRef<'t, T, MANUAL_DROP> {
+    /// In automatic drop mode the item is dropped, in manual drop mode it stays in the pool
+    fn drop(&mut self) {
+        let slot = Self::slot(self);
+        slot.end_borrow_mut(0);
+
+        if !MANUAL_DROP {
+            slot.drop_item();
+        }
+    }
+}
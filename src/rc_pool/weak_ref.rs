@@ -1,76 +1,165 @@
-use super::slot::Slot;
-use crate::{StrongRef, StrongRefTrait, Version, WeakRefTrait, MUT_REF_COUNT};
-
-pub struct WeakRef<'t, T, const MANUAL_DROP: bool> {
-    slot: &'t Slot<T>,
+use super::slot::SlotHeader;
+use crate::{Key, PoolError, StrongRef, StrongRefTrait, Version, WeakRefTrait, MUT_REF_COUNT};
+use core::{cell::UnsafeCell, marker::PhantomData, ptr::NonNull};
+
+/// A weak reference to an item in a pool. The item type may be unsized, see [crate::unsize].
+///
+/// Weak references are Copy and not counted, so [crate::RcPool::shrink_to_fit] never removes a page that a weak
+/// reference has been created into. Use a [crate::CountedWeakRef] or a [Key] if the page should be removable.
+///
+/// Like [StrongRef] it's invariant in `T`:
+/// ```compile_fail
+/// use rc_pool::WeakRef;
+///
+/// fn shorten<'t, 'a>(r: WeakRef<'t, &'static str, true>) -> WeakRef<'t, &'a str, true> {
+///     r
+/// }
+/// ```
+pub struct WeakRef<'t, T: ?Sized, const MANUAL_DROP: bool> {
+    item: NonNull<T>,
     version: Version,
+    phantom: PhantomData<&'t UnsafeCell<T>>, // Invariant in T
 }
 
-impl<'t, T, const MANUAL_DROP: bool> WeakRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> WeakRef<'t, T, MANUAL_DROP> {
+    /// Creates a weak reference to the current item of an occupied slot
+    #[must_use]
+    pub(crate) fn new(item: NonNull<T>) -> Self {
+        let r = Self::new_untracked(item);
+        r.slot().page().weak_refs().set(true);
+        r
+    }
+
+    /// Creates a weak reference to the current item of an occupied slot without marking its page as having
+    /// weak references. The reference must not be handed out, see [crate::CountedWeakRef].
     #[must_use]
-    pub(crate) fn new(slot: &'t Slot<T>) -> Self {
+    pub(crate) fn new_untracked(item: NonNull<T>) -> Self {
         Self {
-            slot,
-            version: slot.version.get(),
+            item,
+            version: unsafe { SlotHeader::of(item.as_ptr()) }.version.get(),
+            phantom: PhantomData,
         }
     }
 
     #[must_use]
-    pub fn try_take_item(&self) -> Option<T> {
-        if self.is_valid() && self.slot.count.get() == 0 {
-            Some(self.slot.take_item())
+    pub(crate) fn slot(&self) -> &'t SlotHeader {
+        unsafe { SlotHeader::of(self.item.as_ptr()) }
+    }
+
+    /// Returns a strong reference to the item, or an error if it has been removed or is mutably borrowed
+    pub fn try_strong(&self) -> Result<StrongRef<'t, T, MANUAL_DROP>, PoolError> {
+        if !self.is_valid() {
+            Err(PoolError::Removed)
+        } else if self.slot().count.get() == MUT_REF_COUNT {
+            Err(PoolError::MutablyBorrowed)
         } else {
-            None
+            Ok(StrongRef::new(self.item))
+        }
+    }
+
+    /// Returns an error unless the item is still in the pool and there are no strong references to it
+    fn check_unreferenced(&self) -> Result<&'t SlotHeader, PoolError> {
+        if !self.is_valid() {
+            return Err(PoolError::Removed);
+        }
+
+        match self.slot().count.get() {
+            0 => Ok(self.slot()),
+            MUT_REF_COUNT => Err(PoolError::MutablyBorrowed),
+            strong_count => Err(PoolError::NotUnique {
+                strong_count: strong_count as usize,
+            }),
         }
     }
 
+    /// Removes and drops the item with the destructor of its concrete type, if there are no strong references to it
+    pub fn try_drop_item(&self) -> Result<(), PoolError> {
+        self.check_unreferenced()?.drop_item();
+        Ok(())
+    }
+
+    pub fn drop_item(&self) {
+        if let Err(e) = self.try_drop_item() {
+            panic!("{e}")
+        }
+    }
+
+    /// Returns a compact key identifying the same item, which can be resolved through the pool
     #[must_use]
-    pub fn take_item(&self) -> T {
-        self.try_take_item()
-            .expect("Can't take item with references!")
+    pub fn key(&self) -> Key {
+        Key::new(self.slot(), self.version)
     }
 
+    /// Converts the reference to a reference to an unsized type, see [StrongRef::unsize_unchecked].
+    /// Use the safe [crate::unsize] macro instead.
+    ///
+    /// # Safety
+    /// The returned pointer must point to the same item and only differ from `item` by its metadata,
+    /// which is guaranteed if it's created by an unsizing coercion.
+    #[doc(hidden)]
     #[must_use]
-    pub fn try_drop_item(&self) -> bool {
-        if self.is_valid() && self.slot.count.get() == 0 {
-            self.slot.take_item();
-            true
-        } else {
-            false
+    pub unsafe fn unsize_unchecked<U: ?Sized>(
+        self,
+        f: impl FnOnce(*const UnsafeCell<T>) -> *const UnsafeCell<U>,
+    ) -> WeakRef<'t, U, MANUAL_DROP> {
+        let item = f(self.item.as_ptr() as *const UnsafeCell<T>) as *mut U;
+        debug_assert!(item as *const u8 == self.item.as_ptr() as *const u8);
+
+        WeakRef {
+            item: NonNull::new_unchecked(item),
+            version: self.version,
+            phantom: PhantomData,
         }
     }
 
-    pub fn drop_item(&self) {
-        assert!(self.try_drop_item(), "Can't drop item with references!");
+    /// Converts the reference to a reference to the same item as type `U`, which must be the item's type
+    #[must_use]
+    pub(crate) unsafe fn cast<U>(self) -> WeakRef<'t, U, MANUAL_DROP> {
+        WeakRef {
+            item: self.item.cast(),
+            version: self.version,
+            phantom: PhantomData,
+        }
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> WeakRefTrait for WeakRef<'t, T, MANUAL_DROP> {
+impl<'t, T, const MANUAL_DROP: bool> WeakRef<'t, T, MANUAL_DROP> {
+    /// Removes the item from the pool and returns it, if there are no strong references to it and it isn't pinned
+    pub fn try_take_item(&self) -> Result<T, PoolError> {
+        let slot = self.check_unreferenced()?;
+
+        if slot.is_pinned() {
+            return Err(PoolError::Pinned);
+        }
+
+        Ok(unsafe { slot.take_item() })
+    }
+
+    #[must_use]
+    pub fn take_item(&self) -> T {
+        self.try_take_item().unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> WeakRefTrait for WeakRef<'t, T, MANUAL_DROP> {
     type Target = T;
     type Strong = StrongRef<'t, T, MANUAL_DROP>;
 
-    #[must_use]
+    /// Panics if the item is mutably borrowed, see [WeakRef::try_strong]
     fn strong(&self) -> Option<Self::Strong> {
-        if self.is_valid() {
-            assert_ne!(
-                self.slot.count.get(),
-                MUT_REF_COUNT,
-                "Already borrowed as mutable!"
-            );
-
-            Some(StrongRef::new(self.slot))
-        } else {
-            None
+        match self.try_strong() {
+            Ok(r) => Some(r),
+            Err(PoolError::Removed) => None,
+            Err(e) => panic!("{e}"),
         }
     }
 
-    #[must_use]
     fn is_valid(&self) -> bool {
-        self.version == self.slot.version.get()
+        self.version == self.slot().version.get()
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> From<StrongRef<'t, T, MANUAL_DROP>>
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> From<StrongRef<'t, T, MANUAL_DROP>>
     for WeakRef<'t, T, MANUAL_DROP>
 {
     fn from(r: StrongRef<'t, T, MANUAL_DROP>) -> Self {
@@ -78,38 +167,40 @@ impl<'t, T, const MANUAL_DROP: bool> From<StrongRef<'t, T, MANUAL_DROP>>
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> PartialEq for WeakRef<'t, T, MANUAL_DROP> {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> From<WeakRef<'t, T, MANUAL_DROP>> for Key {
+    fn from(r: WeakRef<'t, T, MANUAL_DROP>) -> Self {
+        r.key()
+    }
+}
+
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> PartialEq for WeakRef<'t, T, MANUAL_DROP> {
     fn eq(&self, other: &Self) -> bool {
-        std::ptr::eq(self.slot as *const Slot<T>, other.slot as *const Slot<T>)
+        self.item.as_ptr() as *const u8 == other.item.as_ptr() as *const u8
             && self.version == other.version
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> Eq for WeakRef<'t, T, MANUAL_DROP> {}
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Eq for WeakRef<'t, T, MANUAL_DROP> {}
 
-impl<'t, T, const MANUAL_DROP: bool> Clone for WeakRef<'t, T, MANUAL_DROP> {
-    #[must_use]
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Clone for WeakRef<'t, T, MANUAL_DROP> {
     fn clone(&self) -> Self {
-        Self {
-            slot: self.slot,
-            version: self.version,
-        }
+        *self
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> Copy for WeakRef<'t, T, MANUAL_DROP> {}
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> Copy for WeakRef<'t, T, MANUAL_DROP> {}
 
-impl<'t, T, const MANUAL_DROP: bool> std::hash::Hash for WeakRef<'t, T, MANUAL_DROP> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        (self.slot as *const Slot<T>).hash(state);
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> core::hash::Hash for WeakRef<'t, T, MANUAL_DROP> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.item.as_ptr() as *const u8).hash(state);
         self.version.hash(state);
     }
 }
 
-impl<'t, T, const MANUAL_DROP: bool> std::fmt::Debug for WeakRef<'t, T, MANUAL_DROP> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'t, T: ?Sized, const MANUAL_DROP: bool> core::fmt::Debug for WeakRef<'t, T, MANUAL_DROP> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("WeakRef")
-            .field("slot", &(self.slot as *const Slot<T>))
+            .field("item", &(self.item.as_ptr() as *const u8))
             .field("version", &self.version)
             .finish()
     }
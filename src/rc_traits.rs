@@ -1,6 +1,6 @@
-use std::{
+use alloc::rc::{Rc, Weak};
+use core::{
     ops::{Deref, DerefMut},
-    rc::{Rc, Weak},
     slice::Iter,
 };
 
@@ -27,7 +27,7 @@ pub trait StrongRefTrait: Deref {
 }
 
 pub trait WeakRefTrait {
-    type Target;
+    type Target: ?Sized;
     type Strong: StrongRefTrait<Target = Self::Target>;
 
     #[must_use]
@@ -51,7 +51,6 @@ impl<T> StrongRefTrait for Rc<T> {
         Rc::strong_count(self)
     }
 
-    #[must_use]
     fn get_mut(&mut self) -> Option<&mut Self::Target> {
         Rc::get_mut(self)
     }
@@ -71,11 +70,11 @@ impl<T> WeakRefTrait for Weak<T> {
 }
 
 pub trait WeakSliceExt<T> {
-    fn iter_strong(&self) -> StrongIterator<T>;
+    fn iter_strong(&self) -> StrongIterator<'_, T>;
 }
 
 impl<V: AsRef<[T]>, T> WeakSliceExt<T> for V {
-    fn iter_strong(&self) -> StrongIterator<T> {
+    fn iter_strong(&self) -> StrongIterator<'_, T> {
         StrongIterator {
             iter: self.as_ref().iter(),
         }
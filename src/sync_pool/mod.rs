@@ -0,0 +1,8 @@
+pub use pool::*;
+pub use strong_ref::*;
+pub use weak_ref::*;
+
+mod pool;
+mod slot;
+mod strong_ref;
+mod weak_ref;
@@ -0,0 +1,300 @@
+use super::slot::{state, SyncSlot};
+use crate::{Either, Index, Pool, SyncStrongRef};
+use alloc::boxed::Box;
+use core::{
+    marker::PhantomData,
+    ptr::{null_mut, slice_from_raw_parts_mut},
+    sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering::*},
+};
+
+/// Maximum number of pages, page `n` has `first_page_len * 2^n` slots
+const MAX_PAGES: usize = 32;
+
+/// Slot id marking the end of the free list
+const NIL: Index = Index::MAX;
+
+pub(crate) struct SyncPoolHeader<T> {
+    pages: [AtomicPtr<SyncSlot<T>>; MAX_PAGES],
+    page_count: AtomicUsize,
+    first_page_len: Index,
+    free_list: AtomicU64, // Tag (upper 32 bits) and id (lower 32 bits) of the first free slot, the tag prevents ABA
+    len: AtomicUsize,
+}
+
+impl<T> SyncPoolHeader<T> {
+    /// Returns the id of the first slot in a page, or None if it doesn't fit in an [Index]
+    #[must_use]
+    fn first_id(&self, page: usize) -> Option<u64> {
+        let first_id = ((1u64 << page) - 1) * self.first_page_len as u64;
+        (first_id < NIL as u64).then_some(first_id)
+    }
+
+    /// Returns the number of slots in a page, the last possible page is truncated so that all ids are below [NIL]
+    #[must_use]
+    fn page_len(&self, page: usize) -> Index {
+        let first_id = self.first_id(page).unwrap();
+        ((self.first_page_len as u64) << page).min(NIL as u64 - first_id) as Index
+    }
+
+    #[must_use]
+    pub(crate) fn page_count(&self) -> usize {
+        self.page_count.load(Acquire)
+    }
+
+    #[must_use]
+    pub(crate) fn capacity(&self) -> usize {
+        self.first_id(self.page_count())
+            .map_or(NIL as usize, |id| id as usize)
+    }
+
+    #[must_use]
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Relaxed)
+    }
+
+    /// Returns the slot with the given id, which must be in an added page
+    #[must_use]
+    pub(crate) fn slot(&self, id: Index) -> &SyncSlot<T> {
+        let page = (id as u64 / self.first_page_len as u64 + 1).ilog2() as usize;
+        let index = id as u64 - self.first_id(page).unwrap();
+        unsafe { &*self.pages[page].load(Acquire).add(index as usize) }
+    }
+
+    /// Removes the first slot from the free list, returns None if there are no free slots
+    #[must_use]
+    fn pop_free_slot(&self) -> Option<&SyncSlot<T>> {
+        let mut head = self.free_list.load(Acquire);
+
+        loop {
+            let id = head as Index;
+
+            if id == NIL {
+                return None;
+            }
+
+            let slot = self.slot(id);
+            let next = slot.next_free.load(Relaxed);
+            let new_head = state(((head >> 32) as u32).wrapping_add(1), next);
+
+            match self
+                .free_list
+                .compare_exchange_weak(head, new_head, Acquire, Acquire)
+            {
+                Ok(_) => return Some(slot),
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    /// Puts a chain of free slots, linked from `first` to `last`, first in the free list
+    fn push_free_slots(&self, first: Index, last: &SyncSlot<T>) {
+        let mut head = self.free_list.load(Relaxed);
+
+        loop {
+            last.next_free.store(head as Index, Relaxed);
+            let new_head = state(((head >> 32) as u32).wrapping_add(1), first);
+
+            match self
+                .free_list
+                .compare_exchange_weak(head, new_head, Release, Relaxed)
+            {
+                Ok(_) => return,
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    /// Puts a slot, whose item has already been taken, first in the free list
+    pub(crate) fn release(&self, slot: &SyncSlot<T>) {
+        self.len.fetch_sub(1, Relaxed);
+        self.push_free_slots(slot.id, slot);
+    }
+
+    /// Adds the next page and puts its slots in the free list. If another thread is already adding the page,
+    /// waits until it's added instead. If two threads start adding the same page at the same time, only one of
+    /// the pages is kept. Returns false if the slot ids have run out.
+    #[must_use]
+    fn add_page(&self) -> bool {
+        let page = self.page_count();
+
+        let Some(first_id) = (page < MAX_PAGES).then(|| self.first_id(page)).flatten() else {
+            return false;
+        };
+
+        if !self.pages[page].load(Acquire).is_null() {
+            self.wait_for_page(page);
+            return true;
+        }
+
+        let first_id = first_id as Index;
+        let len = self.page_len(page);
+
+        let slots: Box<[SyncSlot<T>]> = (first_id..first_id + len)
+            .map(|id| SyncSlot::new(id, if id + 1 < first_id + len { id + 1 } else { NIL }, self))
+            .collect();
+
+        let slots = Box::into_raw(slots) as *mut SyncSlot<T>;
+
+        if self.pages[page]
+            .compare_exchange(null_mut(), slots, AcqRel, Acquire)
+            .is_err()
+        {
+            drop(unsafe { Box::from_raw(slice_from_raw_parts_mut(slots, len as usize)) });
+            self.wait_for_page(page);
+            return true;
+        }
+
+        self.push_free_slots(first_id, unsafe { &*slots.add(len as usize - 1) });
+        self.page_count.store(page + 1, Release);
+        true
+    }
+
+    /// Waits until another thread has published the slots of a page it has stored in `pages`
+    fn wait_for_page(&self, page: usize) {
+        while self.page_count() <= page {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// A thread safe version of [crate::RcPool]. Slot versions and strong counts are updated atomically,
+/// and free slots are kept in a lock-free list, so items can be inserted, referenced and removed from any thread.
+///
+/// Each added page has twice as many slots as the previous one, and pages are never free'd before the pool is
+/// dropped. Slot versions are 32 bits, so a [SyncWeakRef](crate::SyncWeakRef) could become valid again if its
+/// slot is re-used 2^31 times.
+pub struct SyncRcPool<T, const MANUAL_DROP: bool> {
+    header: Box<SyncPoolHeader<T>>,
+}
+
+unsafe impl<T: Send + Sync, const MANUAL_DROP: bool> Send for SyncRcPool<T, MANUAL_DROP> {}
+unsafe impl<T: Send + Sync, const MANUAL_DROP: bool> Sync for SyncRcPool<T, MANUAL_DROP> {}
+
+impl<T, const MANUAL_DROP: bool> SyncRcPool<T, MANUAL_DROP> {
+    /// Creates a pool whose first page has `page_len` slots
+    #[must_use]
+    pub fn new(page_len: Index) -> Self {
+        let header = Box::new(SyncPoolHeader {
+            pages: [const { AtomicPtr::new(null_mut()) }; MAX_PAGES],
+            page_count: AtomicUsize::new(0),
+            first_page_len: page_len.max(1),
+            free_list: AtomicU64::new(state(0, NIL)),
+            len: AtomicUsize::new(0),
+        });
+
+        assert!(header.add_page(), "Pool is full!");
+        Self { header }
+    }
+
+    /// Returns the number of live items in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.header.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.header.capacity()
+    }
+
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.header.page_count()
+    }
+
+    /// Inserts a new item into the pool
+    /// If there is a free slot, creates and returns a strong reference to that slot,
+    /// otherwise returns the item
+    #[must_use]
+    pub fn try_insert(&self, value: T) -> Either<SyncStrongRef<'_, T, MANUAL_DROP>, T> {
+        let Some(slot) = self.header.pop_free_slot() else {
+            return Either::Right(value);
+        };
+
+        unsafe { slot.set_value(value) };
+        self.header.len.fetch_add(1, Relaxed);
+        Either::Left(SyncStrongRef::new(slot))
+    }
+
+    /// Inserts a new item into the pool
+    /// If there is a free slot, creates and returns a strong reference to that slot,
+    /// otherwise a new page with twice the slots of the last page is added and the item is placed inside it.
+    /// Panics if the slot ids have run out.
+    #[must_use]
+    pub fn insert(&self, mut value: T) -> SyncStrongRef<'_, T, MANUAL_DROP> {
+        loop {
+            match self.try_insert(value) {
+                Either::Left(r) => return r,
+
+                Either::Right(v) => {
+                    assert!(self.header.add_page(), "Pool is full!");
+                    value = v;
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over strong references to all live items.
+    /// Items inserted or removed by other threads during iteration may or may not be included.
+    #[must_use]
+    pub fn iter(&self) -> SyncRcPoolIterator<'_, T, MANUAL_DROP> {
+        SyncRcPoolIterator {
+            header: &self.header,
+            id: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, const MANUAL_DROP: bool> Pool for SyncRcPool<T, MANUAL_DROP> {
+    type Item = T;
+    type Ref<'t> = SyncStrongRef<'t, T, MANUAL_DROP> where Self: 't;
+
+    fn insert(&self, value: T) -> Self::Ref<'_> {
+        SyncRcPool::insert(self, value)
+    }
+}
+
+impl<T, const MANUAL_DROP: bool> Drop for SyncRcPool<T, MANUAL_DROP> {
+    /// Drops all live items in slot order and frees all pages
+    fn drop(&mut self) {
+        for page in 0..self.header.page_count() {
+            let len = self.header.page_len(page) as usize;
+            let slots = self.header.pages[page].load(Acquire);
+            let mut slots = unsafe { Box::from_raw(slice_from_raw_parts_mut(slots, len)) };
+
+            for slot in slots.iter_mut() {
+                unsafe { slot.drop_item() };
+            }
+        }
+    }
+}
+
+pub struct SyncRcPoolIterator<'t, T, const MANUAL_DROP: bool> {
+    header: &'t SyncPoolHeader<T>,
+    id: Index,
+    phantom: PhantomData<&'t mut ()>,
+}
+
+impl<'t, T: 't, const MANUAL_DROP: bool> Iterator for SyncRcPoolIterator<'t, T, MANUAL_DROP> {
+    type Item = SyncStrongRef<'t, T, MANUAL_DROP>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while (self.id as usize) < self.header.capacity() {
+            let slot = self.header.slot(self.id);
+            self.id += 1;
+            let version = slot.version();
+
+            if version & 1 == 0 && slot.acquire(version).is_ok() {
+                return Some(SyncStrongRef::new(slot));
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,113 @@
+use super::pool::SyncPoolHeader;
+use crate::{Count, Index, PoolError, MUT_REF_COUNT};
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering::*},
+};
+
+/// Slot version (upper 32 bits) and strong count (lower 32 bits), packed so that both can be updated atomically
+pub(crate) type State = u64;
+
+#[must_use]
+pub(crate) fn state(version: u32, count: Count) -> State {
+    (version as State) << 32 | count as State
+}
+
+#[must_use]
+pub(crate) fn version(state: State) -> u32 {
+    (state >> 32) as u32
+}
+
+#[must_use]
+pub(crate) fn count(state: State) -> Count {
+    state as Count
+}
+
+pub(crate) struct SyncSlot<T> {
+    item: UnsafeCell<MaybeUninit<T>>,
+    pub(crate) state: AtomicU64,
+    pub(crate) next_free: AtomicU32, // Id of the next slot in the pool's free list, only used while the slot is free
+    pub(crate) id: Index,
+    pool: *const SyncPoolHeader<T>,
+}
+
+impl<T> SyncSlot<T> {
+    #[must_use]
+    pub(crate) fn new(id: Index, next_free: Index, pool: *const SyncPoolHeader<T>) -> Self {
+        Self {
+            item: UnsafeCell::new(MaybeUninit::uninit()),
+            state: AtomicU64::new(state(1, 0)),
+            next_free: AtomicU32::new(next_free),
+            id,
+            pool,
+        }
+    }
+
+    #[must_use]
+    pub(crate) unsafe fn get(&self) -> &T {
+        (*self.item.get()).assume_init_ref()
+    }
+
+    #[must_use]
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) unsafe fn get_mut(&self) -> &mut T {
+        (*self.item.get()).assume_init_mut()
+    }
+
+    #[must_use]
+    pub(crate) fn version(&self) -> u32 {
+        version(self.state.load(Acquire))
+    }
+
+    /// Moves the value into this slot, which must be free and owned by the caller,
+    /// and sets the strong count to one
+    pub(crate) unsafe fn set_value(&self, value: T) {
+        let version = self.version();
+        debug_assert!(version & 1 == 1);
+        (*self.item.get()).write(value);
+        self.state.store(state(version.wrapping_add(1), 1), Release);
+    }
+
+    /// Increments the strong count if the slot still contains the item with the given version
+    /// and it isn't mutably borrowed
+    pub(crate) fn acquire(&self, version: u32) -> Result<(), PoolError> {
+        let mut current = self.state.load(Relaxed);
+
+        loop {
+            if self::version(current) != version {
+                return Err(PoolError::Removed);
+            }
+
+            if count(current) == MUT_REF_COUNT {
+                return Err(PoolError::MutablyBorrowed);
+            }
+
+            match self
+                .state
+                .compare_exchange_weak(current, current + 1, Acquire, Relaxed)
+            {
+                Ok(_) => return Ok(()),
+                Err(s) => current = s,
+            }
+        }
+    }
+
+    /// Takes the item if the slot state is `expected`, which makes the slot free and puts it in the pool's free list.
+    /// Otherwise returns the current state.
+    pub(crate) fn try_take(&self, expected: State) -> Result<T, State> {
+        let free = state(version(expected).wrapping_add(1), 0);
+        self.state
+            .compare_exchange(expected, free, Acquire, Relaxed)?;
+        let value = unsafe { (*self.item.get()).assume_init_read() };
+        unsafe { (*self.pool).release(self) };
+        Ok(value)
+    }
+
+    /// Drops the item if the slot is occupied, without releasing the slot
+    pub(crate) unsafe fn drop_item(&mut self) {
+        if version(*self.state.get_mut()) & 1 == 0 {
+            self.item.get_mut().assume_init_drop()
+        }
+    }
+}
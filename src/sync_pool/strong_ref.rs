@@ -0,0 +1,184 @@
+use super::slot::{count, state, version, SyncSlot};
+use crate::{PoolError, StrongRefTrait, SyncWeakRef, MUT_REF_COUNT};
+use core::{
+    ops::{Deref, DerefMut},
+    sync::atomic::{fence, Ordering::*},
+};
+
+pub struct SyncRefMut<'t, 'u, T, const MANUAL_DROP: bool> {
+    r: &'t mut SyncStrongRef<'u, T, MANUAL_DROP>,
+}
+
+impl<'t, 'u, T, const MANUAL_DROP: bool> Deref for SyncRefMut<'t, 'u, T, MANUAL_DROP> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.r.slot.get() }
+    }
+}
+
+impl<'t, 'u, T, const MANUAL_DROP: bool> DerefMut for SyncRefMut<'t, 'u, T, MANUAL_DROP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.r.slot.get_mut() }
+    }
+}
+
+impl<'t, 'u, T, const MANUAL_DROP: bool> Drop for SyncRefMut<'t, 'u, T, MANUAL_DROP> {
+    fn drop(&mut self) {
+        // We know there's only one strong reference at this point
+        let version = self.r.slot.version();
+        self.r.slot.state.store(state(version, 1), Release);
+    }
+}
+
+/// A thread safe strong reference to an item in a [crate::SyncRcPool]
+pub struct SyncStrongRef<'t, T, const MANUAL_DROP: bool> {
+    slot: &'t SyncSlot<T>,
+}
+
+unsafe impl<'t, T: Send + Sync, const MANUAL_DROP: bool> Send
+    for SyncStrongRef<'t, T, MANUAL_DROP>
+{
+}
+unsafe impl<'t, T: Send + Sync, const MANUAL_DROP: bool> Sync
+    for SyncStrongRef<'t, T, MANUAL_DROP>
+{
+}
+
+impl<'t, T, const MANUAL_DROP: bool> SyncStrongRef<'t, T, MANUAL_DROP> {
+    /// Wraps a slot whose strong count has already been incremented for this reference
+    #[must_use]
+    pub(crate) fn new(slot: &'t SyncSlot<T>) -> Self {
+        Self { slot }
+    }
+
+    #[must_use]
+    pub fn get_mut<'u>(&'u mut self) -> SyncRefMut<'u, 't, T, MANUAL_DROP> {
+        self.try_get_mut().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns a mutable reference to the item, or an error if this isn't the only strong reference
+    pub fn try_get_mut<'u>(&'u mut self) -> Result<SyncRefMut<'u, 't, T, MANUAL_DROP>, PoolError> {
+        let version = self.slot.version();
+
+        match self.slot.state.compare_exchange(
+            state(version, 1),
+            state(version, MUT_REF_COUNT),
+            Acquire,
+            Relaxed,
+        ) {
+            Ok(_) => Ok(SyncRefMut { r: self }),
+            Err(current) => Err(PoolError::NotUnique {
+                strong_count: count(current) as usize,
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn take_item(self) -> T {
+        self.try_take_item().unwrap_or_else(|(e, _)| panic!("{e}"))
+    }
+
+    /// Removes the item from the pool and returns it if this is the only strong reference,
+    /// otherwise returns the error together with `self`
+    pub fn try_take_item(self) -> Result<T, (PoolError, Self)> {
+        match self.slot.try_take(state(self.slot.version(), 1)) {
+            Ok(v) => {
+                core::mem::forget(self);
+                Ok(v)
+            }
+
+            Err(current) => Err((
+                PoolError::NotUnique {
+                    strong_count: count(current) as usize,
+                },
+                self,
+            )),
+        }
+    }
+
+    pub fn drop_item(self) {
+        self.try_drop_item().unwrap_or_else(|(e, _)| panic!("{e}"))
+    }
+
+    /// Drops the item if this is the only strong reference, otherwise returns the error together with `self`
+    pub fn try_drop_item(self) -> Result<(), (PoolError, Self)> {
+        self.try_take_item().map(drop)
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> PartialEq for SyncStrongRef<'t, T, MANUAL_DROP> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.slot, other.slot)
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Eq for SyncStrongRef<'t, T, MANUAL_DROP> {}
+
+impl<'t, T, const MANUAL_DROP: bool> Clone for SyncStrongRef<'t, T, MANUAL_DROP> {
+    fn clone(&self) -> Self {
+        self.slot.state.fetch_add(1, Relaxed);
+        Self::new(self.slot)
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> core::hash::Hash for SyncStrongRef<'t, T, MANUAL_DROP> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.slot as *const SyncSlot<T>).hash(state);
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> core::fmt::Debug for SyncStrongRef<'t, T, MANUAL_DROP> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SyncStrongRef")
+            .field("slot", &(self.slot as *const SyncSlot<T>))
+            .finish()
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Deref for SyncStrongRef<'t, T, MANUAL_DROP> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.slot.get() }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Drop for SyncStrongRef<'t, T, MANUAL_DROP> {
+    /// Decrements the strong count. In automatic drop mode the item is dropped if this was the last strong
+    /// reference, unless a weak reference has been upgraded in the meantime.
+    fn drop(&mut self) {
+        let old = self.slot.state.fetch_sub(1, Release);
+
+        if !MANUAL_DROP && count(old) == 1 {
+            fence(Acquire);
+            let _ = self.slot.try_take(state(version(old), 0));
+        }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> StrongRefTrait for SyncStrongRef<'t, T, MANUAL_DROP> {
+    type Weak = SyncWeakRef<'t, T, MANUAL_DROP>;
+
+    type RefMut<'u> = SyncRefMut<'u, 't, T, MANUAL_DROP> where Self: 'u;
+
+    fn weak(&self) -> Self::Weak {
+        SyncWeakRef::new(self.slot)
+    }
+
+    fn strong_count(&self) -> usize {
+        count(self.slot.state.load(Acquire)) as usize
+    }
+
+    fn get_mut(&mut self) -> Option<Self::RefMut<'_>> {
+        self.try_get_mut().ok()
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> TryFrom<SyncWeakRef<'t, T, MANUAL_DROP>>
+    for SyncStrongRef<'t, T, MANUAL_DROP>
+{
+    type Error = PoolError;
+
+    fn try_from(value: SyncWeakRef<'t, T, MANUAL_DROP>) -> Result<Self, Self::Error> {
+        value.try_strong()
+    }
+}
@@ -0,0 +1,114 @@
+use super::slot::{count, state, version, SyncSlot};
+use crate::{PoolError, StrongRefTrait, SyncStrongRef, WeakRefTrait, MUT_REF_COUNT};
+
+/// A thread safe weak reference to an item in a [crate::SyncRcPool]
+pub struct SyncWeakRef<'t, T, const MANUAL_DROP: bool> {
+    slot: &'t SyncSlot<T>,
+    version: u32,
+}
+
+unsafe impl<'t, T: Send + Sync, const MANUAL_DROP: bool> Send for SyncWeakRef<'t, T, MANUAL_DROP> {}
+unsafe impl<'t, T: Send + Sync, const MANUAL_DROP: bool> Sync for SyncWeakRef<'t, T, MANUAL_DROP> {}
+
+impl<'t, T, const MANUAL_DROP: bool> SyncWeakRef<'t, T, MANUAL_DROP> {
+    #[must_use]
+    pub(crate) fn new(slot: &'t SyncSlot<T>) -> Self {
+        Self {
+            slot,
+            version: slot.version(),
+        }
+    }
+
+    /// Returns a strong reference to the item, or an error if it has been removed or is mutably borrowed
+    pub fn try_strong(&self) -> Result<SyncStrongRef<'t, T, MANUAL_DROP>, PoolError> {
+        self.slot.acquire(self.version)?;
+        Ok(SyncStrongRef::new(self.slot))
+    }
+
+    /// Removes the item from the pool and returns it, if there are no strong references to it
+    pub fn try_take_item(&self) -> Result<T, PoolError> {
+        self.slot
+            .try_take(state(self.version, 0))
+            .map_err(|current| match count(current) {
+                _ if version(current) != self.version => PoolError::Removed,
+                MUT_REF_COUNT => PoolError::MutablyBorrowed,
+                strong_count => PoolError::NotUnique {
+                    strong_count: strong_count as usize,
+                },
+            })
+    }
+
+    #[must_use]
+    pub fn take_item(&self) -> T {
+        self.try_take_item().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Removes and drops the item, if there are no strong references to it
+    pub fn try_drop_item(&self) -> Result<(), PoolError> {
+        self.try_take_item().map(drop)
+    }
+
+    pub fn drop_item(&self) {
+        if let Err(e) = self.try_drop_item() {
+            panic!("{e}")
+        }
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> WeakRefTrait for SyncWeakRef<'t, T, MANUAL_DROP> {
+    type Target = T;
+    type Strong = SyncStrongRef<'t, T, MANUAL_DROP>;
+
+    /// Panics if the item is mutably borrowed, see [SyncWeakRef::try_strong]
+    fn strong(&self) -> Option<Self::Strong> {
+        match self.try_strong() {
+            Ok(r) => Some(r),
+            Err(PoolError::Removed) => None,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.version == self.slot.version()
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> From<SyncStrongRef<'t, T, MANUAL_DROP>>
+    for SyncWeakRef<'t, T, MANUAL_DROP>
+{
+    fn from(r: SyncStrongRef<'t, T, MANUAL_DROP>) -> Self {
+        r.weak()
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> PartialEq for SyncWeakRef<'t, T, MANUAL_DROP> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.slot, other.slot) && self.version == other.version
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Eq for SyncWeakRef<'t, T, MANUAL_DROP> {}
+
+impl<'t, T, const MANUAL_DROP: bool> Clone for SyncWeakRef<'t, T, MANUAL_DROP> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> Copy for SyncWeakRef<'t, T, MANUAL_DROP> {}
+
+impl<'t, T, const MANUAL_DROP: bool> core::hash::Hash for SyncWeakRef<'t, T, MANUAL_DROP> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.slot as *const SyncSlot<T>).hash(state);
+        self.version.hash(state);
+    }
+}
+
+impl<'t, T, const MANUAL_DROP: bool> core::fmt::Debug for SyncWeakRef<'t, T, MANUAL_DROP> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SyncWeakRef")
+            .field("slot", &(self.slot as *const SyncSlot<T>))
+            .field("version", &self.version)
+            .finish()
+    }
+}
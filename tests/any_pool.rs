@@ -0,0 +1,116 @@
+mod common;
+
+use common::Counted;
+use rc_pool::*;
+use std::{any::Any, cell::Cell, rc::Rc};
+
+#[repr(align(64))]
+struct OverAligned(u8);
+
+struct Zst;
+
+#[test]
+fn stores_mixed_sizes_and_alignments() {
+    let pool = AnyPool::<false>::new(4);
+    let a = pool.insert(1u8);
+    let b = pool.insert(2u64);
+    let c = pool.insert([3u16; 13]);
+    let d = pool.insert(String::from("four"));
+    let e = pool.insert(5u32);
+
+    assert_eq!((*a, *b, c[12], d.as_str(), *e), (1, 2, 3, "four", 5));
+    assert_eq!(pool.len(), 5);
+    // The integers share the 8 byte class, the array and the string the 32 byte class
+    assert_eq!(pool.class_count(), 2);
+    assert_eq!(pool.page_count(), 2);
+    assert_eq!(&*b as *const u64 as usize % 8, 0);
+    drop((a, b, c, d, e));
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn stores_zero_sized_items() {
+    let pool = AnyPool::<false>::new(4);
+    let refs: Vec<_> = (0..10).map(|_| pool.insert_any(Zst)).collect();
+    let unit = pool.insert(());
+    assert_eq!(pool.len(), 11);
+
+    for r in refs {
+        assert!(r.downcast::<()>().is_err_and(|r| r.is::<Zst>()));
+    }
+
+    drop(unit);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn aligns_over_aligned_items() {
+    let pool = AnyPool::<false>::new(4);
+    let small = pool.insert(1u8);
+    let refs: Vec<_> = (0..10).map(|i| pool.insert(OverAligned(i))).collect();
+
+    for (i, r) in refs.iter().enumerate() {
+        assert_eq!(&**r as *const OverAligned as usize % 64, 0);
+        assert_eq!(r.0, i as u8);
+    }
+
+    assert_eq!(*small, 1);
+}
+
+#[test]
+fn downcasts_to_the_item_type_only() {
+    let pool = AnyPool::<true>::new(4);
+    let r = pool.insert_any(7u32);
+    let weak = r.weak();
+    assert_eq!(weak.item_type_id(), std::any::TypeId::of::<u32>());
+
+    let r = r.downcast::<i32>().unwrap_err();
+    let r = r.downcast::<u64>().unwrap_err();
+    assert_eq!(r.strong_count(), 1);
+    let r = r.downcast::<u32>().unwrap();
+    assert_eq!(*r, 7);
+
+    assert!(weak.downcast::<i32>().is_err());
+    let typed = weak.downcast::<u32>().unwrap();
+    assert_eq!(*typed.strong().unwrap(), 7);
+    drop(r);
+
+    // Type-erased weak references can be downcast also after the item has been removed
+    assert_eq!(typed.take_item(), 7);
+    assert!(!weak.is_valid());
+    assert!(weak.strong().is_none());
+    assert!(weak.downcast::<u32>().is_ok_and(|w| !w.is_valid()));
+    assert!(weak.downcast::<String>().is_err());
+}
+
+#[test]
+fn drops_remaining_items_with_the_pool() {
+    let drops = Rc::new(Cell::new(0));
+
+    {
+        let pool = AnyPool::<true>::new(2);
+        let counted = |_| Counted(drops.clone());
+        (0..5).map(counted).for_each(|c| drop(pool.insert(c)));
+        (0..3).for_each(|_| drop(pool.insert((Counted(drops.clone()), 0u64, [0u8; 40]))));
+        let r: StrongRef<dyn Any, true> = pool.insert_any(Counted(drops.clone())).into_strong();
+        r.drop_item();
+        assert_eq!(drops.get(), 1);
+        assert_eq!(pool.len(), 8);
+    }
+
+    assert_eq!(drops.get(), 9);
+}
+
+#[test]
+fn reports_items_too_large_for_a_page() {
+    let pool = AnyPool::<true>::new(Index::MAX);
+    assert!(matches!(pool.try_insert([0u8; 64]), Either::Right(_)));
+    assert_eq!(pool.page_count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Page too large!")]
+fn panics_when_inserting_items_too_large_for_a_page() {
+    let pool = AnyPool::<true>::new(Index::MAX);
+    let _ = pool.insert([0u8; 64]);
+}
@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use rc_pool::{GlobalPageAllocator, PageAllocator};
+use std::{
+    alloc::Layout,
+    cell::Cell,
+    ptr::NonNull,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc,
+    },
+};
+
+/// A page allocator which counts the pages and bytes it has allocated and not yet deallocated
+#[derive(Clone, Default)]
+pub struct CountingAllocator {
+    pub pages: Rc<Cell<usize>>,
+    pub bytes: Rc<Cell<usize>>,
+}
+
+unsafe impl PageAllocator for CountingAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.pages.set(self.pages.get() + 1);
+        self.bytes.set(self.bytes.get() + layout.size());
+        GlobalPageAllocator.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.pages.set(self.pages.get() - 1);
+        self.bytes.set(self.bytes.get() - layout.size());
+        GlobalPageAllocator.deallocate(ptr, layout)
+    }
+}
+
+/// A counter shared by [Counted] items
+pub trait DropCounter {
+    fn increment(&self);
+}
+
+impl DropCounter for Rc<Cell<usize>> {
+    fn increment(&self) {
+        self.set(self.get() + 1);
+    }
+}
+
+impl DropCounter for Arc<AtomicUsize> {
+    fn increment(&self) {
+        self.fetch_add(1, Relaxed);
+    }
+}
+
+/// An item which counts how many times it has been dropped, with an `Arc<AtomicUsize>` counter if it's
+/// shared between threads
+pub struct Counted<C: DropCounter = Rc<Cell<usize>>>(pub C);
+
+impl<C: DropCounter> Drop for Counted<C> {
+    fn drop(&mut self) {
+        self.0.increment();
+    }
+}
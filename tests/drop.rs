@@ -0,0 +1,76 @@
+use rc_pool::*;
+use std::{
+    cell::{Cell, RefCell},
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+};
+
+/// An item which records its id in a shared log when it's dropped
+struct Logged {
+    id: u32,
+    log: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Drop for Logged {
+    fn drop(&mut self) {
+        self.log.borrow_mut().push(self.id);
+    }
+}
+
+/// An item whose destructor counts its calls and then panics
+struct Panicking(Rc<Cell<usize>>);
+
+impl Drop for Panicking {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+        panic!("Dropped!");
+    }
+}
+
+#[test]
+fn dropping_the_pool_drops_each_live_item_once_in_page_then_slot_order() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let pool = RcPool::<Logged, true>::new(4);
+
+    let weak: Vec<_> = (0..10)
+        .map(|id| {
+            pool.insert(Logged {
+                id,
+                log: log.clone(),
+            })
+            .weak()
+        })
+        .collect();
+
+    // Removed items are dropped right away, and not again with the pool
+    weak[2].drop_item();
+    weak[7].drop_item();
+    assert_eq!(*log.borrow(), [2, 7]);
+
+    // Newer pages come first, and items within a page are in slot order
+    let order: Vec<_> = pool.iter().map(|r| r.id).collect();
+    assert_eq!(order, [8, 9, 4, 5, 6, 0, 1, 3]);
+
+    drop(weak);
+    drop(pool);
+    assert_eq!(log.borrow()[2..], order);
+}
+
+#[test]
+fn panicking_destructor_releases_the_slot() {
+    let drops = Rc::new(Cell::new(0));
+    let pool = RcPool::<Panicking, true>::new(4);
+    let r = pool.insert(Panicking(drops.clone()));
+    let key = r.key();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| r.drop_item()));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 1);
+
+    assert!(pool.weak(key).is_none());
+    assert_eq!(pool.iter().count(), 0);
+    assert_eq!(pool.len(), 0);
+
+    drop(pool);
+    assert_eq!(drops.get(), 1);
+}
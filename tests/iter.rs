@@ -0,0 +1,101 @@
+use rc_pool::*;
+
+/// Slot indices around the 64-slot words of the occupancy bitmap
+const KEPT: [usize; 10] = [0, 1, 62, 63, 64, 65, 127, 128, 191, 199];
+
+/// Creates a pool with one page of 200 slots, in which only the items at the [KEPT] indices are left
+fn pool() -> RcPool<usize, true> {
+    let pool = RcPool::new(200);
+    let weak: Vec<_> = (0..200).map(|i| pool.insert(i).weak()).collect();
+
+    for (i, w) in weak.iter().enumerate() {
+        if !KEPT.contains(&i) {
+            assert_eq!(w.take_item(), i);
+        }
+    }
+
+    drop(weak);
+    assert_eq!(pool.page_count(), 1);
+    pool
+}
+
+#[test]
+fn iterates_across_bitmap_words() {
+    let mut pool = pool();
+    assert!(pool.values().copied().eq(KEPT));
+    assert!(pool.values().rev().copied().eq(KEPT.into_iter().rev()));
+    assert!(pool.iter().map(|r| *r).eq(KEPT));
+    assert!(pool.iter().rev().map(|r| *r).eq(KEPT.into_iter().rev()));
+    assert!(pool.iter_weak().map(|w| *w.strong().unwrap()).eq(KEPT));
+    assert!(pool.iter_mut().rev().map(|v| *v).eq(KEPT.into_iter().rev()));
+}
+
+#[test]
+fn iterates_from_both_ends() {
+    let mut pool = pool();
+    let mut values = pool.values();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+
+    for i in 0.. {
+        assert_eq!(values.len(), KEPT.len() - front.len() - back.len());
+
+        let next = if i % 3 == 0 {
+            values.next_back().map(|v| back.push(*v))
+        } else {
+            values.next().map(|v| front.push(*v))
+        };
+
+        if next.is_none() {
+            break;
+        }
+    }
+
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, KEPT);
+    assert_eq!(values.len(), 0);
+    assert_eq!(values.next(), None);
+    assert_eq!(values.next_back(), None);
+}
+
+#[test]
+fn iterates_over_pages_from_both_ends() {
+    let pool = RcPool::<usize, true>::new(100);
+    let refs: Vec<_> = (0..250).map(|i| pool.insert(i)).collect();
+    assert_eq!(pool.page_count(), 3);
+
+    let forward: Vec<_> = pool.iter().map(|r| *r).collect();
+    let mut backward: Vec<_> = pool.iter().rev().map(|r| *r).collect();
+    backward.reverse();
+    assert_eq!(forward, backward);
+
+    let mut sorted = forward.clone();
+    sorted.sort_unstable();
+    assert!(sorted.into_iter().eq(0..250));
+
+    let mut iter = pool.iter();
+    assert_eq!(*iter.next().unwrap(), forward[0]);
+    assert_eq!(*iter.next_back().unwrap(), forward[249]);
+    assert_eq!(iter.len(), 248);
+    assert!(iter.map(|r| *r).eq(forward[1..249].iter().copied()));
+    drop(refs);
+}
+
+#[test]
+fn len_skips_borrowed_items() {
+    let pool = RcPool::<usize, true>::new(130);
+    let mut refs: Vec<_> = (0..130).map(|i| pool.insert(i)).collect();
+    let (first, rest) = refs.split_at_mut(1);
+    let _a = first[0].get_mut();
+    let _b = rest[99].get_mut();
+
+    let iter = pool.iter();
+    assert_eq!(iter.len(), 128);
+    assert_eq!(iter.count(), 128);
+    assert!(pool.iter().rev().all(|r| *r != 0 && *r != 100));
+
+    let weak = pool.iter_weak();
+    assert_eq!(weak.len(), 130);
+    assert_eq!(weak.count(), 130);
+}
@@ -0,0 +1,112 @@
+use rc_pool::*;
+
+#[test]
+fn keys_resolve_after_shrink() {
+    let pool = RcPool::<u32, true>::new(4);
+    let refs: Vec<_> = (0..16).map(|i| pool.insert(i)).collect();
+    let keys: Vec<_> = refs.iter().map(|r| r.key()).collect();
+    let weak: Vec<_> = refs.iter().map(|r| r.counted_weak()).collect();
+    drop(refs);
+
+    // Empties the second and fourth page
+    for w in weak[4..8].iter().chain(&weak[12..]) {
+        w.drop_item();
+    }
+
+    drop(weak);
+    pool.shrink_to_fit();
+    assert_eq!(pool.page_count(), 2);
+
+    for (i, key) in keys.iter().enumerate() {
+        let kept = i < 4 || (8..12).contains(&i);
+        assert_eq!(pool.get(*key).map(|r| *r), kept.then_some(i as u32));
+        assert_eq!(pool.weak(*key).is_some(), kept);
+        assert_eq!(Key::from_bits(key.to_bits()), *key);
+    }
+}
+
+#[test]
+fn reused_ids_dont_resolve_old_keys() {
+    let pool = RcPool::<u32, true>::new(4);
+    let weak: Vec<_> = (0..8).map(|i| pool.insert(i).counted_weak()).collect();
+    let keys: Vec<_> = weak.iter().map(|w| w.key()).collect();
+
+    for w in &weak[4..] {
+        w.drop_item();
+    }
+
+    drop(weak);
+    pool.shrink_to_fit();
+
+    // The new page re-uses the ids of the removed page
+    let refs: Vec<_> = (100..104).map(|i| pool.insert(i)).collect();
+    let new_keys: Vec<_> = refs.iter().map(|r| r.key()).collect();
+    let mut ids: Vec<_> = new_keys.iter().map(|k| k.to_bits() >> 32).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, [4, 5, 6, 7]);
+
+    for key in &keys[4..] {
+        assert_eq!(pool.get(*key), None);
+        assert!(new_keys.iter().all(|k| k != key));
+    }
+
+    for (r, key) in refs.iter().zip(&new_keys) {
+        assert_eq!(pool.get(*key).as_ref(), Some(r));
+    }
+
+    for (i, key) in keys[..4].iter().enumerate() {
+        assert_eq!(*pool.get(*key).unwrap(), i as u32);
+    }
+}
+
+#[test]
+fn repeated_growth_and_shrinking_reuses_ids() {
+    let pool = RcPool::<u32, true>::new(8);
+    let mut old_keys = Vec::new();
+
+    for round in 0..1000 {
+        let weak: Vec<_> = (0..40)
+            .map(|i| pool.insert(round * 40 + i).counted_weak())
+            .collect();
+        let keys: Vec<_> = weak.iter().map(|w| w.key()).collect();
+        assert!(keys.iter().all(|k| k.to_bits() >> 32 < 48));
+        assert!(old_keys.iter().all(|k| pool.get(*k).is_none()));
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(*pool.get(*key).unwrap(), round * 40 + i as u32);
+        }
+
+        weak.iter().for_each(|w| w.drop_item());
+        drop(weak);
+        pool.shrink_to_fit();
+        assert_eq!(pool.capacity(), 8);
+        old_keys = keys;
+    }
+}
+
+#[test]
+fn get_skips_mutably_borrowed_items() {
+    let pool = RcPool::<u32, false>::new(4);
+    let mut r = pool.insert(1);
+    let key = r.key();
+    let mut item = r.get_mut();
+    *item = 2;
+    assert_eq!(pool.get(key), None);
+    assert_eq!(pool.try_get(key), Err(PoolError::MutablyBorrowed));
+
+    drop(item);
+    assert_eq!(pool.get(key).as_deref(), Some(&2));
+
+    let mut buffer = [const { std::mem::MaybeUninit::uninit() }; 16];
+    let pool = StaticRcPool::<u32, false>::new(&mut buffer);
+    let Either::Left(mut r) = pool.try_insert(1) else {
+        panic!("Pool is full!")
+    };
+
+    let key = r.key();
+    let item = r.get_mut();
+    assert_eq!(pool.get(key), None);
+    assert_eq!(pool.try_get(key), Err(PoolError::MutablyBorrowed));
+    drop(item);
+    assert_eq!(pool.get(key).as_deref(), Some(&1));
+}
@@ -0,0 +1,41 @@
+use rc_pool::*;
+
+#[test]
+fn reports_the_page_limit() {
+    let pool = RcPool::<u32, false>::new(2);
+    pool.set_limits(PoolLimits {
+        max_capacity: None,
+        max_pages: Some(2),
+    });
+
+    let refs: Vec<_> = (0..4).map(|i| pool.insert(i)).collect();
+    let e = pool.checked_insert(4).unwrap_err();
+    assert_eq!(e.reason, PoolError::CapacityExceeded);
+    assert_eq!(e.limit, Some(PoolLimit::Pages(2)));
+    assert_eq!(
+        e.to_string(),
+        "Pool is full, it has reached its limit of 2 pages!"
+    );
+    assert_eq!(e.into_inner(), 4);
+    assert_eq!(refs.len(), pool.len());
+}
+
+#[test]
+fn reports_the_capacity_limit() {
+    let pool = RcPool::<u32, false>::new(4);
+    pool.set_limits(PoolLimits {
+        max_capacity: Some(6),
+        max_pages: Some(10),
+    });
+
+    let refs: Vec<_> = (0..6).map(|i| pool.insert(i)).collect();
+    assert_eq!(pool.capacity(), 6);
+    let e = pool.checked_insert(6).unwrap_err();
+    assert_eq!(e.limit, Some(PoolLimit::Capacity(6)));
+    assert_eq!(
+        e.to_string(),
+        "Pool is full, it has reached its limit of 6 slots!"
+    );
+    assert_eq!(PoolError::from(e), PoolError::CapacityExceeded);
+    drop(refs);
+}
@@ -0,0 +1,58 @@
+use rc_pool::*;
+
+struct Item {
+    name: String,
+    values: [u32; 2],
+}
+
+fn item() -> Item {
+    Item {
+        name: "item".to_string(),
+        values: [1, 2],
+    }
+}
+
+#[test]
+fn mapped_refs_count_as_strong_references() {
+    let pool = RcPool::<Item, false>::new(4);
+    let r = pool.insert(item());
+    let weak = r.weak();
+
+    let name = StrongRef::map(r, |item| item.name.as_str());
+    assert_eq!(&*name, "item");
+    assert_eq!(MappedRef::strong(&name).strong_count(), 1);
+
+    let second = MappedRef::map(name.clone(), |name| &name[1..]);
+    assert_eq!(&*second, "tem");
+    assert_eq!(MappedRef::strong(&name).strong_count(), 2);
+
+    drop(name);
+    assert!(weak.is_valid());
+    assert_eq!(MappedRef::strong(&second).strong_count(), 1);
+
+    let r = MappedRef::into_strong(second);
+    assert_eq!(r.strong_count(), 1);
+    assert_eq!(r.values, [1, 2]);
+
+    drop(r);
+    assert!(!weak.is_valid());
+}
+
+#[test]
+fn mapped_ref_muts_keep_the_item_mutably_borrowed() {
+    let pool = RcPool::<Item, false>::new(4);
+    let mut r = pool.insert(item());
+    let weak = r.weak();
+
+    {
+        let values = RefMut::map(r.get_mut(), |item| &mut item.values);
+        let mut second = MappedRefMut::map(values, |values| &mut values[1]);
+        *second += 1;
+
+        assert_eq!(weak.try_strong().err(), Some(PoolError::MutablyBorrowed));
+    }
+
+    assert_eq!(r.strong_count(), 1);
+    assert_eq!(weak.try_strong().unwrap().values, [1, 3]);
+    assert_eq!(r.strong_count(), 1);
+}
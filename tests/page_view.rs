@@ -0,0 +1,62 @@
+use rc_pool::*;
+
+/// Creates a pool of 3 pages with the items `0..10`, of which 2 and 7 have been removed
+fn pool() -> RcPool<u32, true> {
+    let pool = RcPool::new(4);
+    let weak: Vec<_> = (0..10).map(|i| pool.insert(i).weak()).collect();
+    weak[2].drop_item();
+    weak[7].drop_item();
+    drop(weak);
+    pool
+}
+
+#[test]
+fn page_views_cover_every_live_item_once() {
+    let mut pool = pool();
+    let mut items = Vec::new();
+    let mut slot_ids = Vec::new();
+
+    for view in pool.page_views() {
+        let occupied = view.occupancy().iter().map(|w| w.count_ones()).sum::<u32>();
+        assert_eq!(view.len(), occupied);
+        assert_eq!(view.iter().count(), view.len() as usize);
+
+        for i in 0..view.capacity() {
+            assert_eq!(view.get(i).is_some(), view.is_occupied(i));
+        }
+
+        items.extend(view.iter().copied());
+        slot_ids.extend(view.slot_ids());
+    }
+
+    // Same order as iter, and no slot id belongs to more than one page
+    assert_eq!(items, pool.iter().map(|r| *r).collect::<Vec<_>>());
+    items.sort_unstable();
+    assert_eq!(items, [0, 1, 3, 4, 5, 6, 8, 9]);
+
+    let len = slot_ids.len();
+    slot_ids.sort_unstable();
+    slot_ids.dedup();
+    assert_eq!(slot_ids.len(), len);
+}
+
+#[test]
+fn mutable_page_views_cover_every_unpinned_item_once() {
+    let mut pool = pool();
+    drop(pool.insert_pinned(10));
+
+    for mut view in pool.page_views_mut() {
+        for i in 0..view.capacity() {
+            let is_pinned = view.get(i) == Some(&10);
+            assert_eq!(view.get_mut(i).is_some(), view.is_occupied(i) && !is_pinned);
+        }
+
+        for item in view {
+            *item += 100;
+        }
+    }
+
+    let mut items: Vec<_> = pool.iter().map(|r| *r).collect();
+    items.sort_unstable();
+    assert_eq!(items, [10, 100, 101, 103, 104, 105, 106, 108, 109]);
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "rayon")]
+
+use rayon::iter::ParallelIterator;
+use rc_pool::*;
+
+#[test]
+fn par_iter_mut_visits_every_unpinned_item_once() {
+    let mut pool = RcPool::<u64, true>::new(8);
+    let weak: Vec<_> = (0..100).map(|i| pool.insert(i).weak()).collect();
+    for weak in weak.iter().step_by(10) {
+        weak.drop_item();
+    }
+    drop(weak);
+    drop(pool.insert_pinned(1000));
+
+    assert_eq!(pool.par_iter_mut().count(), 90);
+    pool.par_iter_mut().for_each(|item| *item += 1000);
+
+    let mut items: Vec<_> = pool.iter().map(|r| *r).collect();
+    items.sort_unstable();
+    let expected = (0..100).filter(|i| i % 10 != 0).map(|i| i + 1000);
+    assert_eq!(items[0], 1000);
+    assert!(items[1..].iter().copied().eq(expected));
+}
+
+#[test]
+fn par_for_each_mutates_every_item() {
+    let mut pool = RcPool::<u64, true>::new(8);
+    for i in 0..50 {
+        drop(pool.insert(i));
+    }
+
+    pool.par_for_each(|item| *item *= 2);
+    assert_eq!(
+        pool.par_iter_mut().map(|item| *item).sum::<u64>(),
+        2 * (0..50).sum::<u64>()
+    );
+}
@@ -0,0 +1,72 @@
+use rc_pool::*;
+use std::marker::PhantomPinned;
+
+struct Pinned {
+    value: u32,
+    _pin: PhantomPinned,
+}
+
+fn pinned(value: u32) -> Pinned {
+    Pinned {
+        value,
+        _pin: PhantomPinned,
+    }
+}
+
+#[test]
+fn refuses_to_move_pinned_items() {
+    let pool = RcPool::<Pinned, true>::new(4);
+    let r = pool.insert_pinned(pinned(1));
+    let weak = StrongRef::pin_weak(&r);
+    let key = StrongRef::pin_key(&r);
+    assert_eq!(weak.key(), key);
+    drop(r);
+
+    assert_eq!(weak.try_take_item().err(), Some(PoolError::Pinned));
+    let (e, r) = pool.get(key).unwrap().try_take_item().err().unwrap();
+    assert_eq!(e, PoolError::Pinned);
+    assert_eq!(r.value, 1);
+    drop(r);
+
+    // Pinned items can still be dropped in place
+    weak.drop_item();
+    assert!(!weak.is_valid());
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn refuses_to_mutably_borrow_pinned_items() {
+    let pool = RcPool::<Pinned, false>::new(4);
+    let mut r = pool.insert_pinned(pinned(1));
+    let mut strong = StrongRef::pin_weak(&r).strong().unwrap();
+    assert!(matches!(strong.try_get_mut(), Err(PoolError::Pinned)));
+    drop(strong);
+
+    let mut strong = pool.get(StrongRef::pin_key(&r)).unwrap();
+    assert!(matches!(strong.try_get_mut(), Err(PoolError::Pinned)));
+    drop(strong);
+
+    let mut item = StrongRef::get_pin_mut(&mut r);
+    unsafe { item.as_mut().get_unchecked_mut().value = 2 };
+    drop(item);
+    assert_eq!(r.value, 2);
+}
+
+#[test]
+fn drain_and_iter_mut_skip_pinned_items() {
+    let mut pool = RcPool::<Pinned, true>::new(4);
+    let key = StrongRef::pin_key(&pool.insert_pinned(pinned(1)));
+    drop(pool.insert(pinned(2)));
+    drop(pool.insert(pinned(3)));
+
+    for item in pool.iter_mut() {
+        item.value *= 10;
+    }
+
+    assert_eq!(pool.get(key).unwrap().value, 1);
+    let mut drained: Vec<_> = pool.drain().map(|p| p.value).collect();
+    drained.sort_unstable();
+    assert_eq!(drained, [20, 30]);
+    assert_eq!(pool.len(), 1);
+    assert_eq!(pool.get(key).unwrap().value, 1);
+}
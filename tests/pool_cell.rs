@@ -0,0 +1,87 @@
+use rc_pool::*;
+
+#[test]
+fn shared_borrows_coexist_and_block_mutable_ones() {
+    let pool = RcPool::<PoolCell<u32>, false>::new(4);
+    let r = pool.insert(PoolCell::new(1));
+
+    let a = r.borrow();
+    let b = r.borrow();
+    assert_eq!(*a + *b, 2);
+    assert_eq!(r.borrow_count(), 2);
+    assert_eq!(r.try_borrow_mut().err(), Some(PoolError::Borrowed));
+
+    drop(a);
+    assert_eq!(r.try_borrow_mut().err(), Some(PoolError::Borrowed));
+
+    drop(b);
+    assert_eq!(r.borrow_count(), 0);
+    *r.borrow_mut() += 1;
+    assert_eq!(*r.borrow(), 2);
+}
+
+#[test]
+fn mutable_borrows_are_exclusive() {
+    let pool = RcPool::<PoolCell<u32>, false>::new(4);
+    let r = pool.insert(PoolCell::new(1));
+
+    let mut m = r.borrow_mut();
+    *m = 5;
+    assert!(r.is_mutably_borrowed());
+    assert_eq!(r.borrow_count(), 0);
+    assert_eq!(r.try_borrow().err(), Some(PoolError::MutablyBorrowed));
+    assert_eq!(r.try_borrow_mut().err(), Some(PoolError::MutablyBorrowed));
+
+    drop(m);
+    assert!(!r.is_mutably_borrowed());
+    assert_eq!(*r.borrow(), 5);
+}
+
+#[test]
+#[should_panic(expected = "Already borrowed!")]
+fn borrow_mut_panics_while_borrowed() {
+    let pool = RcPool::<PoolCell<u32>, false>::new(4);
+    let r = pool.insert(PoolCell::new(1));
+
+    let _a = r.borrow();
+    let _m = r.borrow_mut();
+}
+
+#[test]
+fn guards_keep_the_item_alive() {
+    let pool = RcPool::<PoolCell<u32>, false>::new(4);
+    let r = pool.insert(PoolCell::new(1));
+    let weak = r.weak();
+
+    let a = r.borrow();
+    assert_eq!(r.strong_count(), 2);
+
+    drop(r);
+    assert!(weak.is_valid());
+    assert_eq!(*a, 1);
+
+    drop(a);
+    assert!(!weak.is_valid());
+}
+
+#[test]
+fn weak_references_borrow_regardless_of_the_strong_count() {
+    let pool = RcPool::<PoolCell<u32>, true>::new(4);
+    let r = pool.insert(PoolCell::new(1));
+    let other = r.clone();
+    let weak = r.weak();
+
+    *weak.try_borrow_mut().unwrap() += 1;
+    assert_eq!(*weak.try_borrow().unwrap(), 2);
+    assert_eq!(*other.borrow(), 2);
+
+    {
+        let _m = other.borrow_mut();
+        assert_eq!(weak.try_borrow().err(), Some(PoolError::MutablyBorrowed));
+    }
+
+    drop((r, other));
+    weak.drop_item();
+    assert_eq!(weak.try_borrow().err(), Some(PoolError::Removed));
+    assert_eq!(weak.try_borrow_mut().err(), Some(PoolError::Removed));
+}
@@ -0,0 +1,83 @@
+use rc_pool::*;
+
+#[test]
+fn drain_yields_only_unshared_items() {
+    let pool = RcPool::<u32, true>::new(4);
+    let weak: Vec<_> = (0..6).map(|i| pool.insert(i).weak()).collect();
+    let shared = weak[1].try_strong().unwrap();
+    let mut borrowed = weak[4].try_strong().unwrap();
+    let _m = borrowed.get_mut();
+
+    let mut drained: Vec<_> = pool.drain().collect();
+    drained.sort_unstable();
+    assert_eq!(drained, [0, 2, 3, 5]);
+
+    for (i, weak) in weak.iter().enumerate() {
+        assert_eq!(weak.is_valid(), i == 1 || i == 4);
+    }
+    assert_eq!(*shared, 1);
+}
+
+#[test]
+fn items_not_yielded_by_drain_stay_in_the_pool() {
+    let pool = RcPool::<u32, true>::new(4);
+    let weak: Vec<_> = (0..6).map(|i| pool.insert(i).weak()).collect();
+
+    assert_eq!(pool.drain().take(2).count(), 2);
+    assert_eq!(weak.iter().filter(|weak| weak.is_valid()).count(), 4);
+    assert_eq!(pool.drain().count(), 4);
+    assert_eq!(pool.iter().count(), 0);
+}
+
+#[test]
+fn retain_skips_shared_and_borrowed_items() {
+    let pool = RcPool::<u32, true>::new(4);
+    let weak: Vec<_> = (0..6).map(|i| pool.insert(i).weak()).collect();
+    let keys: Vec<_> = weak.iter().map(|weak| weak.key()).collect();
+    let shared = weak[0].try_strong().unwrap();
+    let mut borrowed = weak[2].try_strong().unwrap();
+    let _m = borrowed.get_mut();
+
+    let mut seen = Vec::new();
+    let skipped = pool.retain(|i| {
+        seen.push(*i);
+        i % 2 == 1
+    });
+
+    // The mutably borrowed item isn't passed to the closure
+    seen.sort_unstable();
+    assert_eq!(seen, [0, 1, 3, 4, 5]);
+
+    let mut skipped: Vec<_> = skipped.iter().map(|weak| weak.key()).collect();
+    skipped.sort_unstable_by_key(|key| keys.iter().position(|k| k == key));
+    assert_eq!(skipped, [keys[0], keys[2]]);
+
+    for (i, weak) in weak.iter().enumerate() {
+        assert_eq!(weak.is_valid(), i != 4);
+    }
+    assert_eq!(pool.try_get(keys[4]).err(), Some(PoolError::Removed));
+    assert_eq!(*shared, 0);
+}
+
+#[test]
+fn clear_bumps_the_versions_of_removed_items() {
+    let pool = RcPool::<u32, true>::new(4);
+    let weak: Vec<_> = (0..3).map(|i| pool.insert(i).weak()).collect();
+    let keys: Vec<_> = weak.iter().map(|weak| weak.key()).collect();
+    let shared = weak[1].try_strong().unwrap();
+
+    let skipped = pool.clear();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].key(), keys[1]);
+
+    assert!(!weak[0].is_valid() && !weak[2].is_valid());
+    assert_eq!(pool.try_get(keys[0]).err(), Some(PoolError::Removed));
+
+    // New items re-use the slots with new versions, so the old keys stay invalid
+    let new: Vec<_> = (3..5).map(|i| pool.insert(i)).collect();
+    for r in &new {
+        assert!(!keys.contains(&r.key()));
+    }
+    assert_eq!(pool.try_get(keys[2]).err(), Some(PoolError::Removed));
+    assert_eq!(*pool.try_get(keys[1]).unwrap(), *shared);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::CountingAllocator;
+use rc_pool::*;
+
+#[test]
+fn frees_empty_pages() {
+    let allocator = CountingAllocator::default();
+    let pool = RcPool::<u32, false>::new_in(4, allocator.clone());
+    let refs: Vec<_> = (0..12).map(|i| pool.insert(i)).collect();
+    assert_eq!(allocator.pages.get(), 3);
+
+    let kept = refs[5].clone();
+    drop(refs);
+    pool.shrink_to_fit();
+    assert_eq!(allocator.pages.get(), 1);
+    assert_eq!((pool.len(), pool.capacity(), pool.page_count()), (1, 4, 1));
+    assert_eq!(*kept, 5);
+
+    drop(kept);
+    pool.shrink_to_fit();
+    assert_eq!(allocator.pages.get(), 1);
+    assert_eq!(pool.capacity(), 4);
+
+    let refs: Vec<_> = (0..6).map(|i| pool.insert(i)).collect();
+    assert!(refs.iter().map(|r| **r).eq(0..6));
+    assert_eq!(pool.page_count(), 2);
+}
+
+#[test]
+fn weak_refs_into_removed_pages_are_invalid() {
+    let allocator = CountingAllocator::default();
+    let pool = RcPool::<u32, true>::new_in(4, allocator.clone());
+    let weak: Vec<_> = (0..12).map(|i| pool.insert(i).counted_weak()).collect();
+
+    for w in &weak[4..] {
+        w.drop_item();
+    }
+
+    // The emptied pages are removed from the pool, but kept in memory while weak references point into them
+    pool.shrink_to_fit();
+    assert_eq!(pool.page_count(), 1);
+    assert_eq!(pool.capacity(), 4);
+    assert_eq!(allocator.pages.get(), 3);
+
+    for w in &weak[4..] {
+        assert!(!w.is_valid());
+        assert!(w.strong().is_none());
+        assert_eq!(w.try_take_item(), Err(PoolError::Removed));
+        assert_eq!(pool.get(w.key()), None);
+    }
+
+    assert!(weak[..4].iter().all(|w| w.is_valid()));
+
+    // Once the weak references are dropped, the next call frees the pages
+    let bytes = pool.allocated_bytes();
+    let live = weak[..4].to_vec();
+    drop(weak);
+    assert_eq!(allocator.pages.get(), 3);
+    assert_eq!(pool.allocated_bytes(), bytes);
+
+    pool.shrink_to_fit();
+    assert_eq!(allocator.pages.get(), 1);
+    assert!(pool.allocated_bytes() < bytes);
+    assert!(live.iter().all(|w| w.is_valid()));
+}
+
+#[test]
+fn pages_with_weak_refs_are_freed_with_the_pool() {
+    let allocator = CountingAllocator::default();
+
+    {
+        let pool = RcPool::<u32, true>::new_in(4, allocator.clone());
+        let weak: Vec<_> = (0..12).map(|i| pool.insert(i).counted_weak()).collect();
+        weak.iter().for_each(|w| w.drop_item());
+        pool.shrink_to_fit();
+        assert_eq!(pool.page_count(), 1);
+        assert_eq!(allocator.pages.get(), 3);
+        assert!(weak.iter().all(|w| !w.is_valid()));
+        std::mem::forget(weak);
+    }
+
+    assert_eq!(allocator.pages.get(), 0);
+}
+
+#[test]
+fn doesnt_remove_pages_while_iterating() {
+    let pool = RcPool::<u32, true>::new(4);
+    let weak: Vec<_> = (0..12).map(|i| pool.insert(i).counted_weak()).collect();
+
+    for w in &weak[..10] {
+        w.drop_item();
+    }
+
+    drop(weak);
+    let mut iter = pool.iter_weak();
+    let first = iter.next().unwrap();
+    pool.shrink_to_fit();
+    assert_eq!(pool.page_count(), 3);
+    assert_eq!(iter.count(), 1);
+
+    let stats = pool.page_stats();
+    pool.shrink_to_fit();
+    assert_eq!(stats.count(), 3);
+
+    pool.shrink_to_fit();
+    assert_eq!(pool.page_count(), 1);
+    assert!(first.is_valid());
+}
+
+#[test]
+fn pages_with_uncounted_weak_refs_are_kept() {
+    let allocator = CountingAllocator::default();
+    let pool = RcPool::<u32, true>::new_in(4, allocator.clone());
+    let counted: Vec<_> = (0..12).map(|i| pool.insert(i).counted_weak()).collect();
+    let weak = counted[4].weak();
+    let copy = weak;
+    counted.iter().for_each(|w| w.drop_item());
+    drop(counted);
+
+    // Only the page that the Copy weak references point into is kept
+    pool.shrink_to_fit();
+    assert_eq!(pool.page_count(), 2);
+    assert_eq!(allocator.pages.get(), 2);
+    assert!(!weak.is_valid() && !copy.is_valid());
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::Counted;
+use rc_pool::*;
+use std::{
+    cell::Cell,
+    mem::{size_of, MaybeUninit},
+    rc::Rc,
+};
+
+fn buffer<T, const N: usize>() -> [MaybeUninit<StaticSlot<T>>; N] {
+    [const { MaybeUninit::uninit() }; N]
+}
+
+#[test]
+fn capacity_is_the_buffer_minus_header_and_bitmap() {
+    let mut small = buffer::<u64, 16>();
+    let mut large = buffer::<u64, 1000>();
+    let small = StaticRcPool::<u64, true>::new(&mut small);
+    let large = StaticRcPool::<u64, true>::new(&mut large);
+
+    // The page header takes a few slots at the start and the occupancy bitmap, one bit per slot, the last ones
+    let header_slots = 16 - small.capacity() - 1;
+    assert!(header_slots > 0);
+    let slots = 1000 - header_slots;
+    let bitmap_bytes = slots.div_ceil(usize::BITS as usize) * size_of::<usize>();
+    let bitmap_slots = bitmap_bytes.div_ceil(size_of::<StaticSlot<u64>>());
+    assert_eq!(large.capacity(), slots - bitmap_slots);
+    assert_eq!(large.free_slots(), large.capacity());
+
+    let inserted = (0..).map_while(|i| small.try_insert(i).take_left()).count();
+    assert_eq!(inserted, small.capacity());
+    assert_eq!(small.free_slots(), 0);
+}
+
+#[test]
+fn gives_the_value_back_when_full() {
+    let mut buffer = buffer::<String, 16>();
+    let pool = StaticRcPool::<String, false>::new(&mut buffer);
+    let refs: Vec<_> = (0..pool.capacity())
+        .map(|i| pool.try_insert(i.to_string()).take_left().unwrap())
+        .collect();
+
+    assert!(matches!(pool.try_insert("full".into()), Either::Right(s) if s == "full"));
+    assert_eq!(pool.len(), refs.len());
+    assert!(refs.iter().enumerate().all(|(i, r)| **r == i.to_string()));
+}
+
+#[test]
+fn reuses_slots_after_removal() {
+    let mut buffer = buffer::<u32, 16>();
+    let pool = StaticRcPool::<u32, true>::new(&mut buffer);
+    let weak: Vec<_> = (0..pool.capacity() as u32)
+        .map(|i| pool.try_insert(i).take_left().unwrap().weak())
+        .collect();
+
+    assert_eq!(weak[1].take_item(), 1);
+    assert!(!weak[1].is_valid());
+    assert_eq!(pool.get(weak[1].key()), None);
+
+    let r = pool.try_insert(100).take_left().unwrap();
+    assert_eq!(pool.free_slots(), 0);
+    assert_eq!(pool.get(r.key()).as_deref(), Some(&100));
+    assert!(!weak[1].is_valid());
+    assert!(weak.iter().enumerate().all(|(i, w)| i == 1 || w.is_valid()));
+}
+
+#[test]
+fn drops_remaining_items_with_the_pool() {
+    let drops = Rc::new(Cell::new(0));
+    let mut buffer = buffer::<Counted, 16>();
+
+    {
+        let pool = StaticRcPool::<Counted, true>::new(&mut buffer);
+
+        for _ in 0..3 {
+            drop(pool.try_insert(Counted(drops.clone())).take_left().unwrap());
+        }
+
+        pool.try_insert(Counted(drops.clone()))
+            .take_left()
+            .unwrap()
+            .drop_item();
+
+        assert_eq!(drops.get(), 1);
+        assert_eq!(pool.len(), 3);
+    }
+
+    assert_eq!(drops.get(), 4);
+}
+
+#[test]
+fn uses_a_buffer_at_any_offset() {
+    let mut buffer = buffer::<u8, 17>();
+    let pool = StaticRcPool::<u8, true>::new(&mut buffer[1..]);
+    let r = pool.try_insert(1).take_left().unwrap();
+    assert_eq!(
+        &*r as *const u8 as usize % std::mem::align_of::<StaticSlot<u8>>(),
+        0
+    );
+    assert_eq!(*r, 1);
+}
+
+#[test]
+#[should_panic(expected = "No room for the page header!")]
+fn panics_if_the_buffer_is_too_small() {
+    let mut buffer = buffer::<u64, 1>();
+    let _pool = StaticRcPool::<u64, true>::new(&mut buffer);
+}
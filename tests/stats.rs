@@ -0,0 +1,96 @@
+mod common;
+
+use common::{Counted, CountingAllocator};
+use rc_pool::*;
+use std::{cell::Cell, rc::Rc};
+
+#[test]
+fn counts_items_and_slots() {
+    let pool = RcPool::<u32, true>::new(10);
+    assert_eq!((pool.len(), pool.capacity(), pool.page_count()), (0, 10, 1));
+    assert!(pool.is_empty());
+
+    let weak: Vec<_> = (0..25).map(|i| pool.insert(i).weak()).collect();
+    assert_eq!(
+        (pool.len(), pool.capacity(), pool.page_count()),
+        (25, 30, 3)
+    );
+    assert_eq!(pool.free_slots(), 5);
+
+    for w in &weak[..8] {
+        w.drop_item();
+    }
+
+    assert_eq!(pool.len(), 17);
+    assert_eq!(pool.free_slots(), 13);
+
+    let stats: Vec<_> = pool.page_stats().collect();
+    assert_eq!(stats.len(), 3);
+    assert!(stats.iter().all(|s| s.capacity == 10));
+    assert_eq!(stats.iter().map(|s| s.len as usize).sum::<usize>(), 17);
+    assert_eq!(
+        stats.iter().map(|s| s.free_slots() as usize).sum::<usize>(),
+        13
+    );
+    assert!(stats.contains(&PageStats {
+        len: 2,
+        capacity: 10
+    }));
+}
+
+#[test]
+fn reports_allocated_bytes() {
+    let allocator = CountingAllocator::default();
+    let pool = RcPool::<u64, false>::new_in(16, allocator.clone());
+    let header_bytes = pool.allocated_bytes() - allocator.bytes.get();
+    assert!(RcPool::<u64, false>::slot_overhead() > 0);
+
+    pool.reserve(100);
+    assert_eq!(pool.allocated_bytes(), header_bytes + allocator.bytes.get());
+    assert!(allocator.bytes.get() >= pool.capacity() * (8 + RcPool::<u64, false>::slot_overhead()));
+}
+
+#[test]
+fn drops_all_items_and_frees_all_pages() {
+    let allocator = CountingAllocator::default();
+    let drops = Rc::new(Cell::new(0));
+
+    {
+        let pool = RcPool::<Counted, true>::new_in(8, allocator.clone());
+        let weak: Vec<_> = (0..20)
+            .map(|_| pool.insert(Counted(drops.clone())).weak())
+            .collect();
+        assert_eq!(allocator.pages.get(), 3);
+
+        weak[0].drop_item();
+        drop(weak[1].take_item());
+        assert_eq!(drops.get(), 2);
+        assert_eq!(pool.len(), 18);
+    }
+
+    assert_eq!(drops.get(), 20);
+    assert_eq!(allocator.pages.get(), 0);
+    assert_eq!(allocator.bytes.get(), 0);
+}
+
+#[test]
+fn automatic_drop_mode_leaves_nothing_behind() {
+    let allocator = CountingAllocator::default();
+    let drops = Rc::new(Cell::new(0));
+
+    {
+        let pool = RcPool::<Counted, false>::new_in(4, allocator.clone());
+        let refs: Vec<_> = (0..10)
+            .map(|_| pool.insert(Counted(drops.clone())))
+            .collect();
+        let clones = refs.clone();
+        drop(refs);
+        assert_eq!(drops.get(), 0);
+        drop(clones);
+        assert_eq!(drops.get(), 10);
+        assert!(pool.is_empty());
+        assert_eq!(pool.capacity(), 12);
+    }
+
+    assert_eq!(allocator.bytes.get(), 0);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::Counted;
+use rc_pool::*;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc, Barrier,
+    },
+    thread,
+};
+
+const THREADS: usize = 8;
+
+#[test]
+fn concurrent_insert_upgrade_take() {
+    const ROUNDS: usize = 20_000;
+    let pool = SyncRcPool::<usize, true>::new(1);
+    let start = Barrier::new(THREADS);
+
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let (pool, start) = (&pool, &start);
+
+            s.spawn(move || {
+                start.wait();
+
+                for i in 0..ROUNDS {
+                    // Each thread holds a few slots at a time, so that slots are popped and pushed back in
+                    // varying orders. A slot popped by two threads at once (for example after an ABA on the
+                    // free list) would have its value overwritten by the other thread.
+                    let value = (t * ROUNDS + i) * 3;
+                    let refs = [
+                        pool.insert(value),
+                        pool.insert(value + 1),
+                        pool.insert(value + 2),
+                    ];
+                    let weak = refs.each_ref().map(|r| r.weak());
+
+                    for (j, (r, w)) in refs.into_iter().zip(&weak).rev().enumerate() {
+                        assert_eq!(*w.strong().unwrap(), value + 2 - j);
+                        assert_eq!(r.take_item(), value + 2 - j);
+                        assert!(!w.is_valid());
+                    }
+                }
+            });
+        }
+    });
+
+    assert!(pool.is_empty());
+    assert_eq!(pool.iter().count(), 0);
+}
+
+#[test]
+fn concurrent_page_adds_keep_all_items() {
+    const ITEMS: usize = 5_000;
+    let pool = SyncRcPool::<usize, true>::new(1);
+
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let pool = &pool;
+                s.spawn(move || {
+                    (0..ITEMS)
+                        .map(|i| pool.insert(t * ITEMS + i))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let refs: Vec<_> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        let mut values: Vec<_> = refs.iter().map(|r| **r).collect();
+        values.sort_unstable();
+        assert!(values.iter().copied().eq(0..THREADS * ITEMS));
+        assert_eq!(pool.len(), THREADS * ITEMS);
+        assert_eq!(pool.iter().count(), THREADS * ITEMS);
+    });
+}
+
+#[test]
+fn concurrent_drops_drop_item_once() {
+    const ROUNDS: usize = 2_000;
+    let drops = Arc::new(AtomicUsize::new(0));
+    let pool = SyncRcPool::<Counted<Arc<AtomicUsize>>, false>::new(4);
+
+    for round in 0..ROUNDS {
+        let r = pool.insert(Counted(drops.clone()));
+        let w = r.weak();
+        let clones: Vec<_> = (0..THREADS).map(|_| r.clone()).collect();
+        drop(r);
+
+        // The last strong reference is dropped by one of the threads, while the others try to upgrade
+        // the weak reference at the same time
+        thread::scope(|s| {
+            for c in clones {
+                s.spawn(move || {
+                    if let Some(r) = w.strong() {
+                        drop(r);
+                    }
+
+                    drop(c);
+                });
+            }
+        });
+
+        assert!(!w.is_valid());
+        assert_eq!(drops.load(Relaxed), round + 1);
+    }
+
+    assert!(pool.is_empty());
+    assert_eq!(pool.capacity(), 4);
+}
@@ -0,0 +1,68 @@
+use rc_pool::*;
+
+#[test]
+fn weak_references_cant_be_upgraded_while_unique() {
+    let pool = RcPool::<u32, false>::new(4);
+    let mut unique = pool.insert_unique(1);
+    let weak = UniqueRef::weak(&unique);
+    let key = UniqueRef::key(&unique);
+
+    *unique += 1;
+    assert_eq!(weak.try_strong().err(), Some(PoolError::MutablyBorrowed));
+    assert_eq!(pool.try_get(key).err(), Some(PoolError::MutablyBorrowed));
+    assert!(weak.is_valid());
+
+    let r = UniqueRef::share(unique);
+    assert_eq!(r.strong_count(), 1);
+    assert_eq!(*weak.try_strong().unwrap(), 2);
+    assert_eq!(*pool.try_get(key).unwrap(), 2);
+    assert_eq!(r.strong_count(), 1);
+}
+
+#[test]
+fn into_unique_requires_the_only_strong_reference() {
+    let pool = RcPool::<u32, false>::new(4);
+    let r = pool.insert(1);
+    let other = r.clone();
+
+    let r = r.into_unique().unwrap_err();
+    assert_eq!(r.strong_count(), 2);
+
+    drop(other);
+    let mut unique = r.into_unique().unwrap();
+    *unique = 3;
+
+    let r: StrongRef<_, false> = unique.into();
+    assert_eq!(*r, 3);
+    assert!(r.is_unique());
+}
+
+#[test]
+fn dropping_a_unique_ref_follows_the_drop_mode() {
+    let pool = RcPool::<u32, false>::new(4);
+    let unique = pool.insert_unique(1);
+    let weak = UniqueRef::weak(&unique);
+    drop(unique);
+    assert!(!weak.is_valid());
+
+    let pool = RcPool::<u32, true>::new(4);
+    let unique = pool.insert_unique(1);
+    let weak = UniqueRef::weak(&unique);
+    drop(unique);
+    assert_eq!(*weak.try_strong().unwrap(), 1);
+
+    let unique = weak.try_strong().unwrap().into_unique().unwrap();
+    UniqueRef::drop_item(unique);
+    assert!(!weak.is_valid());
+}
+
+#[test]
+fn take_item_removes_the_item() {
+    let pool = RcPool::<String, true>::new(4);
+    let unique = pool.insert_unique("item".to_string());
+    let weak = UniqueRef::weak(&unique);
+
+    assert_eq!(UniqueRef::take_item(unique), "item");
+    assert!(!weak.is_valid());
+    assert_eq!(pool.iter().count(), 0);
+}